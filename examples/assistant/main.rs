@@ -25,7 +25,11 @@ mod ui;
 
 use std::{env, fs, io};
 
-use claus::anthropic::{Content, Tool, ToolResult, ToolUse};
+use claus::{
+    anthropic::{Content, Tool, ToolResult, ToolUse},
+    http_response::HttpResponse,
+    retry::RetryPolicy,
+};
 use reqwest::blocking::{Client, Request};
 use serde::Deserialize;
 use tools::{
@@ -106,7 +110,10 @@ fn main() -> io::Result<()> {
             println!("[{}.{}] Claude> {}", offset, idx, item);
 
             // Once everything has been printed, handle actual tool use.
-            if let Content::ToolUse(ToolUse { id, name, input }) = item {
+            if let Content::ToolUse(ToolUse {
+                id, name, input, ..
+            }) = item
+            {
                 match name.as_str() {
                     "web_search" => {
                         let input: WebSearchInput = serde_json::from_value(input).unwrap();
@@ -161,40 +168,40 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-/// Sends an HTTP request with automatic retry logic for rate limiting.
+/// Sends an HTTP request, retrying temporary failures such as rate limiting or overloading.
 pub fn send_request(client: &Client, req: Request) -> Result<String, String> {
-    let mut retries_left = 3;
-    while retries_left > 0 {
+    let policy = RetryPolicy::new().max_attempts(4);
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+
         let response = client
             .execute(req.try_clone().expect("Failed to clone request"))
             .map_err(|e| format!("Failed to send request: {}", e))?;
+        let response = HttpResponse::try_from_reqwest_blocking(response)
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
 
-        if response.status().as_u16() == 429 || response.status().as_u16() == 420 {
-            let retry_after = response
-                .headers()
-                .get("retry-after")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok())
-                .unwrap_or(1);
-
-            retries_left -= 1;
-
-            eprintln!("Rate limit exceeded. Retrying in {} seconds.", retry_after);
-
-            std::thread::sleep(std::time::Duration::from_secs(retry_after));
-        } else {
-            if !response.status().is_success() {
-                let status = response.status();
-                let text = response
-                    .text()
-                    .unwrap_or_else(|err| format!("(failed to fetch response text: {})", err));
-                return Err(format!("Request failed with HTTP {}: {}", status, text));
+        if response.is_success() {
+            return Ok(response.body_text().into_owned());
+        }
+
+        match policy.retry_delay(attempt, &response) {
+            Some(delay) => {
+                eprintln!(
+                    "Request failed with HTTP {}. Retrying in {:.1} seconds.",
+                    response.status,
+                    delay.as_secs_f64()
+                );
+                std::thread::sleep(delay);
+            }
+            None => {
+                return Err(format!(
+                    "Request failed with HTTP {}: {}",
+                    response.status,
+                    response.body_text()
+                ));
             }
-            let body = response
-                .text()
-                .map_err(|e| format!("Failed to read response body: {}", e))?;
-            return Ok(body);
         }
     }
-    Err("Rate limit exceeded.".to_string())
 }
@@ -135,7 +135,7 @@ fn print_stream_event(w: &mut impl Write, event: &StreamEvent) {
                 &format!("[stream] block {} start: ", index),
             );
             match content_block {
-                Content::Text { text } => {
+                Content::Text { text, .. } => {
                     write!(w, "{}", text).expect("write failed");
                 }
                 Content::ToolUse(tu) => {
@@ -157,7 +157,7 @@ fn print_stream_event(w: &mut impl Write, event: &StreamEvent) {
             Delta::ThinkingDelta { thinking } => {
                 write_colored(w, Color::DarkBlue, thinking);
             }
-            Delta::SignatureDelta { .. } => {}
+            Delta::SignatureDelta { .. } | Delta::CitationsDelta { .. } => {}
         },
 
         StreamEvent::ContentBlockStop { index } => {
@@ -206,7 +206,7 @@ fn print_stream_event(w: &mut impl Write, event: &StreamEvent) {
 /// Prints a content block with the given prefix.
 fn print_content(w: &mut impl Write, content: &Content, prefix: &str) {
     match content {
-        Content::Text { text } => {
+        Content::Text { text, .. } => {
             for line in text.lines() {
                 writeln!(w, "{}{}", prefix, line).expect("write failed");
             }
@@ -267,10 +267,31 @@ fn print_content(w: &mut impl Write, content: &Content, prefix: &str) {
             }
         }
 
-        Content::Image => {
+        Content::Image { .. } => {
             writeln!(w, "{}<image>", prefix).expect("write failed");
         }
 
+        Content::Thinking { thinking, .. } => {
+            for line in thinking.lines() {
+                write!(w, "{}", prefix).expect("write failed");
+                write_colored(w, Color::DarkBlue, &format!("{}\n", line));
+            }
+        }
+
+        Content::RedactedThinking { .. } => {
+            writeln!(w, "{}<redacted thinking>", prefix).expect("write failed");
+        }
+
+        Content::Document(document) => {
+            writeln!(
+                w,
+                "{}<document: {}>",
+                prefix,
+                document.title.as_deref().unwrap_or("untitled")
+            )
+            .expect("write failed");
+        }
+
         Content::Unknown => {
             writeln!(w, "{}<unknown content>", prefix).expect("write failed");
         }
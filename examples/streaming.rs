@@ -17,9 +17,10 @@ use std::{
     io::{self, Write},
 };
 
-use claus::anthropic::{Content, Delta, Message, Role, StreamEvent, StreamingMessage};
-use futures::stream::StreamExt;
-use reqwest_eventsource::{Event, EventSource};
+use claus::{
+    anthropic::{Content, Delta, Message, Role, StreamEvent},
+    streaming::{SseDecoder, StreamAccumulator},
+};
 use serde::Deserialize;
 
 /// Configuration structure for simple chat.
@@ -55,124 +56,71 @@ async fn main() {
             .stream(true)
             .build(&api);
 
-        let request_builder = http_req
-            .try_into_reqwest_builder(&client)
-            .expect("failed to create request builder");
-
-        let mut es = EventSource::new(request_builder).expect("failed to create event source");
-
-        let mut assistant_content = Vec::new();
-        let mut current_text = String::new();
-        let mut streaming_message: Option<StreamingMessage> = None;
-
-        while let Some(event) = es.next().await {
-            match event {
-                Ok(Event::Open) => {
-                    // Nothing to do here.
-                }
-                Ok(Event::Message(message)) => {
-                    // Parse the SSE message data using our deserialize_event function
-                    match claus::deserialize_event(message.data.as_bytes()) {
-                        Ok(stream_event) => match stream_event {
-                            StreamEvent::MessageStart { message } => {
-                                print!("Assistant: ");
-                                io::stdout().flush().expect("failed to flush stdout");
-                                streaming_message = Some(message);
-                            }
-                            StreamEvent::ContentBlockStart {
-                                index: _,
-                                content_block,
-                            } => {
-                                match content_block {
-                                    Content::Text { text } => {
-                                        // Display the start of a text message immediately.
-                                        print!("{}", text);
-                                        io::stdout().flush().expect("failed to flush stdout");
-                                        current_text.push_str(&text);
-                                    }
-                                    _ => {
-                                        eprintln!("Other content block: {:?}", content_block);
-                                    }
-                                }
-                            }
-                            StreamEvent::ContentBlockDelta { index, delta } => {
-                                match delta {
-                                    Delta::TextDelta { text } => {
-                                        // Display text immediately as it comes in
-                                        print!("{}", text);
-                                        io::stdout().flush().expect("failed to flush stdout");
-                                        current_text.push_str(&text);
-                                    }
-                                    other_delta => {
-                                        eprintln!(
-                                            "Other delta for block {}: {:?}",
-                                            index, other_delta
-                                        );
-                                    }
-                                }
-                            }
-                            StreamEvent::ContentBlockStop { index: _ } => {
-                                if !current_text.is_empty() {
-                                    // We are relying on the API sending content in order.
-                                    assistant_content
-                                        .push(Content::from_text(current_text.clone()));
-                                    current_text.clear();
-                                }
-                            }
-                            StreamEvent::MessageDelta { delta, usage: _ } => {
-                                // Apply the delta to our streaming message. Note that we don't
-                                // use the usage information at all, this is just a demonstration
-                                // of how to update the message.
-                                if let Some(ref mut msg) = streaming_message {
-                                    msg.update(delta);
-                                } else {
-                                    eprintln!(
-                                        "Received MessageDelta but no streaming message available"
-                                    );
-                                }
-                            }
-                            StreamEvent::MessageStop => {
-                                println!();
-                                break;
-                            }
-                            StreamEvent::Ping => {
-                                // We quietly accept pings.
-                            }
-                            StreamEvent::Error { error } => {
-                                eprintln!("Error event: {:?}", error);
-                                break;
-                            }
-                            StreamEvent::Unknown {
-                                event_type,
-                                contents,
-                            } => {
-                                eprintln!(
-                                    "Unknown event type: {:?}, contents: {:?}",
-                                    String::from_utf8_lossy(&event_type),
-                                    contents
-                                );
-                            }
-                        },
-                        Err(parse_err) => {
-                            eprintln!("Failed to parse event data: {}", parse_err);
-                            eprintln!("Raw data: {}", message.data);
-                        }
+        let mut response = client
+            .execute(http_req.into())
+            .await
+            .expect("failed to send request");
+
+        let mut decoder = SseDecoder::new();
+        let mut accumulator = StreamAccumulator::new();
+
+        'receive: while let Some(chunk) = response.chunk().await.expect("failed to read body") {
+            for stream_event in decoder.feed(&chunk) {
+                let stream_event = match stream_event {
+                    Ok(stream_event) => stream_event,
+                    Err(parse_err) => {
+                        eprintln!("Failed to parse event data: {}", parse_err);
+                        continue;
+                    }
+                };
+
+                // Display text immediately as it comes in, the accumulator takes care of
+                // assembling the complete message.
+                match stream_event {
+                    StreamEvent::MessageStart { .. } => {
+                        print!("Assistant: ");
+                        io::stdout().flush().expect("failed to flush stdout");
                     }
+                    StreamEvent::ContentBlockStart {
+                        content_block: Content::Text { ref text, .. },
+                        ..
+                    }
+                    | StreamEvent::ContentBlockDelta {
+                        delta: Delta::TextDelta { ref text },
+                        ..
+                    } => {
+                        print!("{}", text);
+                        io::stdout().flush().expect("failed to flush stdout");
+                    }
+                    StreamEvent::Unknown {
+                        ref event_type,
+                        ref contents,
+                    } => {
+                        eprintln!(
+                            "Unknown event type: {:?}, contents: {:?}",
+                            String::from_utf8_lossy(event_type),
+                            contents
+                        );
+                    }
+                    _ => {}
                 }
-                Err(err) => {
-                    eprintln!("Error: {}", err);
-                    break;
+
+                if let Err(err) = accumulator.push(stream_event) {
+                    eprintln!("Stream error: {}", err);
+                    break 'receive;
+                }
+
+                if accumulator.is_complete() {
+                    println!();
+                    break 'receive;
                 }
             }
         }
 
         // Add the complete assistant message to our conversation history
-        if !assistant_content.is_empty() {
-            let assistant_message = Message {
-                role: Role::Assistant,
-                content: assistant_content,
-            };
-            messages.push_back(assistant_message);
+        match accumulator.finish() {
+            Ok(response) => messages.push_back(response.message),
+            Err(err) => eprintln!("Incomplete response: {}", err),
         }
     }
 }
@@ -2,8 +2,9 @@
 //!
 //! This module contains types that match the implemented Anthropic API.
 
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, sync::Arc};
 
+use base64::Engine;
 use schemars::{JsonSchema, schema_for};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -17,6 +18,62 @@ pub const DEFAULT_ENDPOINT_HOST: &str = "api.anthropic.com";
 /// Default model to use for requests.
 pub const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
 
+/// A beta feature, enabled through the `anthropic-beta` header.
+///
+/// Betas can be enabled for all requests using [`crate::Api::beta`], or for a single request
+/// using [`crate::MessagesRequestBuilder::beta`]. See
+/// <https://docs.anthropic.com/en/api/beta-headers> for details.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Beta {
+    /// The Files API, including content referencing uploaded files.
+    FilesApi,
+    /// The code execution tool.
+    CodeExecution,
+    /// Automatic context management, e.g. clearing old tool results.
+    ContextManagement,
+    /// A context window of one million tokens, on supported models.
+    Context1M,
+    /// Thinking between tool calls.
+    InterleavedThinking,
+    /// Any other beta, given by its flag.
+    Other(Arc<str>),
+}
+
+impl Beta {
+    /// Returns the flag of the beta, as sent in the `anthropic-beta` header.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Beta::FilesApi => "files-api-2025-04-14",
+            Beta::CodeExecution => "code-execution-2025-05-22",
+            Beta::ContextManagement => "context-management-2025-06-27",
+            Beta::Context1M => "context-1m-2025-08-07",
+            Beta::InterleavedThinking => "interleaved-thinking-2025-05-14",
+            Beta::Other(flag) => flag,
+        }
+    }
+}
+
+impl Display for Beta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Beta {
+    fn from(flag: &str) -> Self {
+        [
+            Beta::FilesApi,
+            Beta::CodeExecution,
+            Beta::ContextManagement,
+            Beta::Context1M,
+            Beta::InterleavedThinking,
+        ]
+        .into_iter()
+        .find(|beta| beta.as_str() == flag)
+        .unwrap_or_else(|| Beta::Other(Arc::from(flag)))
+    }
+}
+
 /// The body of a request to the messages endpoint.
 ///
 /// This type can be used to construct a [`crate::http_request::HttpRequest`] for the `messages`
@@ -29,7 +86,7 @@ pub struct MessagesBody<'a> {
     pub max_tokens: u32,
     /// The system prompt for the conversation.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<&'a str>,
+    pub system: Option<&'a SystemPrompt>,
     /// The messages to include in the request.
     ///
     /// Uses [`im::Vector`] for efficient sharing and cloning of conversation history.
@@ -37,16 +94,268 @@ pub struct MessagesBody<'a> {
     /// Tools available for the model to use.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<&'a im::Vector<Tool>>,
+    /// How the model should use the provided tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<&'a ToolChoice>,
+    /// Extended thinking configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<ThinkingConfig>,
+    /// Amount of randomness injected into the response, between `0.0` and `1.0`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Only sample from the top K options for each subsequent token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Use nucleus sampling with the given cumulative probability cut-off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Custom text sequences that will cause the model to stop generating.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<&'a [String]>,
+    /// Metadata about the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<&'a Metadata>,
+    /// Which service tier to use for the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
     /// Whether to stream the response.
     #[serde(skip_serializing_if = "is_false")]
     pub stream: bool,
 }
 
+/// The body of a request to the token counting endpoint.
+///
+/// Contains the subset of [`MessagesBody`] relevant to the size of the prompt. Usually it is
+/// better to use [`crate::MessagesRequestBuilder::build_count_tokens`] instead.
+#[derive(Debug, Serialize)]
+pub struct CountTokensBody<'a> {
+    /// The model to count tokens for.
+    pub model: &'a str,
+    /// The system prompt for the conversation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<&'a SystemPrompt>,
+    /// The messages to count.
+    pub messages: &'a im::Vector<Message>,
+    /// Tools available for the model to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<&'a im::Vector<Tool>>,
+    /// How the model should use the provided tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<&'a ToolChoice>,
+    /// Extended thinking configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<ThinkingConfig>,
+}
+
+/// The response to a token counting request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TokenCount {
+    /// The total number of tokens across system prompt, messages and tools.
+    pub input_tokens: u32,
+}
+
+/// Metadata about a request.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Metadata {
+    /// An external identifier for the user associated with the request.
+    ///
+    /// Should be an opaque identifier such as a UUID or hash, Anthropic may use it to help detect
+    /// abuse. Do not include any identifying information such as names or email addresses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+impl Metadata {
+    /// Creates metadata with the given user ID.
+    pub fn user_id<S: Into<String>>(user_id: S) -> Self {
+        Self {
+            user_id: Some(user_id.into()),
+        }
+    }
+}
+
+/// Service tier to use for a request.
+///
+/// See <https://docs.anthropic.com/en/api/service-tiers> for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceTier {
+    /// Use priority capacity if available, falling back to standard capacity.
+    Auto,
+    /// Only use standard capacity.
+    StandardOnly,
+}
+
+/// Configuration for extended thinking.
+///
+/// When enabled, the model will produce [`Content::Thinking`] blocks containing its reasoning
+/// before the final answer. See
+/// <https://docs.anthropic.com/en/docs/build-with-claude/extended-thinking> for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThinkingConfig {
+    /// Extended thinking is enabled.
+    Enabled {
+        /// Number of tokens the model may use for thinking.
+        ///
+        /// Must be at least 1024 and less than `max_tokens`.
+        budget_tokens: u32,
+    },
+    /// Extended thinking is disabled.
+    Disabled,
+}
+
+impl ThinkingConfig {
+    /// Creates an enabled thinking configuration with the given budget.
+    pub fn enabled(budget_tokens: u32) -> Self {
+        ThinkingConfig::Enabled { budget_tokens }
+    }
+}
+
 /// Helper function to check if a boolean is false, used with `serde(skip_serializing_if)`.
 fn is_false(value: &bool) -> bool {
     !value
 }
 
+/// A system prompt.
+///
+/// Usually a system prompt is a single string, but it can also be made up of multiple text blocks,
+/// which is required to mark parts of it for caching using [`CacheControl`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SystemPrompt {
+    /// A plain text system prompt.
+    Text(Arc<str>),
+    /// A system prompt made up of text blocks.
+    Blocks(Vec<TextBlock>),
+}
+
+impl SystemPrompt {
+    /// Marks the entire system prompt for caching.
+    ///
+    /// A plain text prompt is converted into a single block. For block prompts, the cache
+    /// breakpoint is set on the last block, which caches all blocks.
+    pub fn with_cache_control(self, cache_control: CacheControl) -> Self {
+        let mut blocks = match self {
+            SystemPrompt::Text(text) => vec![TextBlock::new(text.as_ref())],
+            SystemPrompt::Blocks(blocks) => blocks,
+        };
+        if let Some(last) = blocks.last_mut() {
+            last.cache_control = Some(cache_control);
+        }
+        SystemPrompt::Blocks(blocks)
+    }
+}
+
+impl From<&str> for SystemPrompt {
+    fn from(s: &str) -> Self {
+        SystemPrompt::Text(Arc::from(s))
+    }
+}
+
+impl From<String> for SystemPrompt {
+    fn from(s: String) -> Self {
+        SystemPrompt::Text(Arc::from(s))
+    }
+}
+
+impl From<Arc<str>> for SystemPrompt {
+    fn from(s: Arc<str>) -> Self {
+        SystemPrompt::Text(s)
+    }
+}
+
+impl From<Vec<TextBlock>> for SystemPrompt {
+    fn from(blocks: Vec<TextBlock>) -> Self {
+        SystemPrompt::Blocks(blocks)
+    }
+}
+
+impl Display for SystemPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemPrompt::Text(text) => f.write_str(text),
+            SystemPrompt::Blocks(blocks) => {
+                for (idx, block) in blocks.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("\n")?;
+                    }
+                    f.write_str(&block.text)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A standalone text block, as used in a [`SystemPrompt`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename = "text")]
+pub struct TextBlock {
+    /// The text content.
+    pub text: String,
+    /// Cache breakpoint, see [`CacheControl`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+impl TextBlock {
+    /// Creates a new text block without cache control.
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            cache_control: None,
+        }
+    }
+
+    /// Sets a cache breakpoint on this block.
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = Some(cache_control);
+        self
+    }
+}
+
+/// A prompt caching breakpoint.
+///
+/// Marks the end of a cacheable prompt prefix. The prefix consists of tools, system prompt and
+/// messages, in that order, up to and including the block carrying the breakpoint. At most four
+/// breakpoints may be set per request.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/prompt-caching> for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CacheControl {
+    /// A short-lived cache entry.
+    Ephemeral {
+        /// Time-to-live of the cache entry, defaults to five minutes if not set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl: Option<CacheTtl>,
+    },
+}
+
+impl CacheControl {
+    /// Creates an ephemeral cache breakpoint with the default time-to-live.
+    pub fn ephemeral() -> Self {
+        CacheControl::Ephemeral { ttl: None }
+    }
+
+    /// Creates an ephemeral cache breakpoint with the given time-to-live.
+    pub fn ephemeral_with_ttl(ttl: CacheTtl) -> Self {
+        CacheControl::Ephemeral { ttl: Some(ttl) }
+    }
+}
+
+/// Time-to-live of a cache entry.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum CacheTtl {
+    /// Five minutes, refreshed every time the entry is used.
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    /// One hour, at a higher cache write cost.
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
 /// A role in a conversation.
 ///
 /// The current API specification only supports `user` and `assistant` roles.
@@ -145,6 +454,11 @@ pub struct Tool {
     /// This schema describes the structure and types of the parameters
     /// that the tool expects to receive.
     pub input_schema: Value,
+    /// Cache breakpoint, see [`CacheControl`].
+    ///
+    /// Setting a breakpoint on the last tool caches all tool definitions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
 impl Tool {
@@ -165,8 +479,107 @@ impl Tool {
             name: name.into(),
             description: description.into(),
             input_schema,
+            cache_control: None,
         }
     }
+
+    /// Sets a cache breakpoint on this tool.
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = Some(cache_control);
+        self
+    }
+}
+
+/// How the model should use the provided tools.
+///
+/// See <https://docs.anthropic.com/en/docs/agents-and-tools/tool-use/implement-tool-use#forcing-tool-use>
+/// for details.
+///
+/// # Example
+///
+/// ```
+/// use claus::anthropic::ToolChoice;
+///
+/// let choice = ToolChoice::tool("get_weather").disable_parallel_tool_use();
+///# let serialized = serde_json::to_value(&choice).expect("Should serialize");
+///# assert_eq!(serialized["type"], "tool");
+///# assert_eq!(serialized["name"], "get_weather");
+///# assert_eq!(serialized["disable_parallel_tool_use"], true);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to use any of the tools (the default).
+    Auto {
+        /// Whether the model should output at most one tool use.
+        #[serde(default, skip_serializing_if = "is_false")]
+        disable_parallel_tool_use: bool,
+    },
+    /// The model must use one of the provided tools.
+    Any {
+        /// Whether the model should output exactly one tool use.
+        #[serde(default, skip_serializing_if = "is_false")]
+        disable_parallel_tool_use: bool,
+    },
+    /// The model must use the named tool.
+    Tool {
+        /// The name of the tool to use.
+        name: String,
+        /// Whether the model should output exactly one tool use.
+        #[serde(default, skip_serializing_if = "is_false")]
+        disable_parallel_tool_use: bool,
+    },
+    /// The model must not use any tools.
+    None,
+}
+
+impl ToolChoice {
+    /// Lets the model decide whether to use tools.
+    pub fn auto() -> Self {
+        ToolChoice::Auto {
+            disable_parallel_tool_use: false,
+        }
+    }
+
+    /// Forces the model to use one of the tools.
+    pub fn any() -> Self {
+        ToolChoice::Any {
+            disable_parallel_tool_use: false,
+        }
+    }
+
+    /// Forces the model to use the tool with the given name.
+    pub fn tool<S: Into<String>>(name: S) -> Self {
+        ToolChoice::Tool {
+            name: name.into(),
+            disable_parallel_tool_use: false,
+        }
+    }
+
+    /// Prevents the model from using tools.
+    pub fn none() -> Self {
+        ToolChoice::None
+    }
+
+    /// Prevents the model from using multiple tools in a single response.
+    ///
+    /// Has no effect on [`ToolChoice::None`].
+    pub fn disable_parallel_tool_use(mut self) -> Self {
+        match &mut self {
+            ToolChoice::Auto {
+                disable_parallel_tool_use,
+            }
+            | ToolChoice::Any {
+                disable_parallel_tool_use,
+            }
+            | ToolChoice::Tool {
+                disable_parallel_tool_use,
+                ..
+            } => *disable_parallel_tool_use = true,
+            ToolChoice::None => {}
+        }
+        self
+    }
 }
 
 /// A tool use request from the model.
@@ -180,6 +593,9 @@ pub struct ToolUse {
     pub name: String,
     /// The input parameters for the tool.
     pub input: Value,
+    /// Cache breakpoint, see [`CacheControl`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
 impl Display for ToolUse {
@@ -200,6 +616,9 @@ pub struct ToolResult {
     /// Whether this result represents an error.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Cache breakpoint, see [`CacheControl`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
 /// Content that can be included in a tool result.
@@ -254,6 +673,7 @@ impl ToolResult {
             tool_use_id,
             content: content.into(),
             is_error: None,
+            cache_control: None,
         }
     }
 
@@ -263,6 +683,7 @@ impl ToolResult {
             tool_use_id,
             content: error_content.into(),
             is_error: Some(true),
+            cache_control: None,
         }
     }
 
@@ -310,6 +731,256 @@ pub struct WebSearchResult {
     pub page_age: Option<String>,
 }
 
+/// Media type of an image.
+///
+/// These are the image formats currently accepted by the API.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ImageMediaType {
+    /// `image/jpeg`
+    #[serde(rename = "image/jpeg")]
+    Jpeg,
+    /// `image/png`
+    #[serde(rename = "image/png")]
+    Png,
+    /// `image/gif`
+    #[serde(rename = "image/gif")]
+    Gif,
+    /// `image/webp`
+    #[serde(rename = "image/webp")]
+    Webp,
+}
+
+/// The source of an image content block.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/vision> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    /// Image data embedded directly into the request.
+    Base64 {
+        /// The media type of the encoded image.
+        media_type: ImageMediaType,
+        /// The base64-encoded image data.
+        data: String,
+    },
+    /// Image referenced by URL, fetched by Anthropic's servers.
+    Url {
+        /// The URL of the image.
+        url: String,
+    },
+    /// Image previously uploaded through the Files API, see [`crate::files`].
+    File {
+        /// The ID of the uploaded file.
+        file_id: String,
+    },
+}
+
+/// The source of a document content block.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/pdf-support> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DocumentSource {
+    /// Document data embedded directly into the request, e.g. a PDF.
+    Base64 {
+        /// The media type of the encoded document, usually `application/pdf`.
+        media_type: String,
+        /// The base64-encoded document data.
+        data: String,
+    },
+    /// A plain-text document.
+    Text {
+        /// The media type of the document, usually `text/plain`.
+        media_type: String,
+        /// The document text.
+        data: String,
+    },
+    /// A document made up of custom content blocks.
+    ///
+    /// Citations will refer to the individual blocks instead of character ranges.
+    Content {
+        /// The blocks making up the document.
+        content: Vec<Content>,
+    },
+    /// A PDF referenced by URL, fetched by Anthropic's servers.
+    Url {
+        /// The URL of the document.
+        url: String,
+    },
+    /// A document previously uploaded through the Files API, see [`crate::files`].
+    File {
+        /// The ID of the uploaded file.
+        file_id: String,
+    },
+}
+
+/// Citation configuration of a document.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CitationsConfig {
+    /// Whether the model should cite passages from the document.
+    pub enabled: bool,
+}
+
+/// A document, such as a PDF or a piece of plain text, provided to the model.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Document {
+    /// Where the document data comes from.
+    pub source: DocumentSource,
+    /// Title of the document, passed to the model but not used for citations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Additional context about the document, passed to the model but not used for citations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Citation configuration, citations are disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citations: Option<CitationsConfig>,
+    /// Cache breakpoint, see [`CacheControl`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
+}
+
+impl Document {
+    /// Creates a document from raw PDF data.
+    ///
+    /// The data will be base64-encoded.
+    pub fn from_pdf_bytes<B: AsRef<[u8]>>(data: B) -> Self {
+        Self::from_source(DocumentSource::Base64 {
+            media_type: "application/pdf".to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(data),
+        })
+    }
+
+    /// Creates a plain-text document.
+    pub fn from_text<S: Into<String>>(text: S) -> Self {
+        Self::from_source(DocumentSource::Text {
+            media_type: "text/plain".to_string(),
+            data: text.into(),
+        })
+    }
+
+    /// Creates a document made up of custom content blocks.
+    pub fn from_contents(content: Vec<Content>) -> Self {
+        Self::from_source(DocumentSource::Content { content })
+    }
+
+    /// Creates a document referencing a file uploaded through the Files API.
+    ///
+    /// Requests using it must enable [`Beta::FilesApi`].
+    pub fn from_file<S: Into<String>>(file_id: S) -> Self {
+        Self::from_source(DocumentSource::File {
+            file_id: file_id.into(),
+        })
+    }
+
+    /// Creates a document from a given source, without title, context or citations.
+    pub fn from_source(source: DocumentSource) -> Self {
+        Self {
+            source,
+            title: None,
+            context: None,
+            citations: None,
+            cache_control: None,
+        }
+    }
+
+    /// Sets the title of the document.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the context of the document.
+    pub fn context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Enables or disables citations for the document.
+    pub fn citations(mut self, enabled: bool) -> Self {
+        self.citations = Some(CitationsConfig { enabled });
+        self
+    }
+}
+
+/// A citation, mapping a piece of generated text back to a span of a source.
+///
+/// The location type depends on the kind of the cited document: plain-text documents are cited
+/// by character range, PDFs by page range and custom content documents by block range. All
+/// indices are 0-based, end indices are exclusive.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/citations> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Citation {
+    /// A character range within a plain-text document.
+    CharLocation {
+        /// The cited text, does not count towards output tokens.
+        cited_text: String,
+        /// Index of the cited document among all documents of the request.
+        document_index: u32,
+        /// Title of the cited document, if it had one.
+        document_title: Option<String>,
+        /// Start of the cited range, in characters.
+        start_char_index: u32,
+        /// End of the cited range, in characters.
+        end_char_index: u32,
+    },
+    /// A page range within a PDF document.
+    PageLocation {
+        /// The cited text, does not count towards output tokens.
+        cited_text: String,
+        /// Index of the cited document among all documents of the request.
+        document_index: u32,
+        /// Title of the cited document, if it had one.
+        document_title: Option<String>,
+        /// First cited page, 1-based.
+        start_page_number: u32,
+        /// Page after the last cited page, 1-based.
+        end_page_number: u32,
+    },
+    /// A block range within a custom content document.
+    ContentBlockLocation {
+        /// The cited text, does not count towards output tokens.
+        cited_text: String,
+        /// Index of the cited document among all documents of the request.
+        document_index: u32,
+        /// Title of the cited document, if it had one.
+        document_title: Option<String>,
+        /// Index of the first cited block.
+        start_block_index: u32,
+        /// End of the cited block range.
+        end_block_index: u32,
+    },
+    /// A passage from a web search result.
+    WebSearchResultLocation {
+        /// The cited text.
+        cited_text: String,
+        /// URL of the cited page.
+        url: String,
+        /// Title of the cited page.
+        title: Option<String>,
+        /// Opaque reference to the cited passage.
+        encrypted_index: String,
+    },
+    /// Catch-all for unrecognized citation types.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Citation {
+    /// Returns the cited text, if known.
+    pub fn cited_text(&self) -> Option<&str> {
+        match self {
+            Citation::CharLocation { cited_text, .. }
+            | Citation::PageLocation { cited_text, .. }
+            | Citation::ContentBlockLocation { cited_text, .. }
+            | Citation::WebSearchResultLocation { cited_text, .. } => Some(cited_text),
+            Citation::Unknown => None,
+        }
+    }
+}
+
 /// Content pieces that make up a message.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -318,11 +989,40 @@ pub enum Content {
     Text {
         /// The text content.
         text: String,
+        /// Sources supporting the text, if citations were enabled on a document.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        citations: Option<Vec<Citation>>,
+        /// Cache breakpoint, see [`CacheControl`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
     },
     /// Image content.
+    Image {
+        /// Where the image data comes from.
+        source: ImageSource,
+        /// Cache breakpoint, see [`CacheControl`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    /// Document content, e.g. a PDF.
+    Document(Document),
+    /// The model's reasoning, if extended thinking is enabled.
     ///
-    /// TODO: At the moment, images are not supported.
-    Image,
+    /// Thinking blocks must be passed back to the API unaltered, including their signature,
+    /// when continuing a conversation that involves tool use.
+    Thinking {
+        /// The (possibly summarized) reasoning of the model.
+        thinking: String,
+        /// Cryptographic signature verifying the block was produced by the model.
+        signature: String,
+    },
+    /// Reasoning that has been encrypted by the API for safety reasons.
+    ///
+    /// Like [`Content::Thinking`], these blocks must be passed back to the API unaltered.
+    RedactedThinking {
+        /// The encrypted reasoning.
+        data: String,
+    },
     /// Tool use content.
     ToolUse(ToolUse),
     /// Tool result content.
@@ -365,8 +1065,14 @@ pub enum Content {
 impl Display for Content {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Content::Text { text } => f.write_str(text),
-            Content::Image => f.write_str("<image>"),
+            Content::Text { text, .. } => f.write_str(text),
+            Content::Image { .. } => f.write_str("<image>"),
+            Content::Document(document) => match document.title {
+                Some(ref title) => write!(f, "<document:{title}>"),
+                None => f.write_str("<document>"),
+            },
+            Content::Thinking { thinking, .. } => write!(f, "<thinking>{thinking}</thinking>"),
+            Content::RedactedThinking { .. } => f.write_str("<redacted_thinking>"),
             Content::ToolUse(tool_use) => tool_use.fmt(f),
             Content::ToolResult(tool_result) => tool_result.fmt(f),
             Content::ServerToolUse { id, name, .. } => write!(f, "<server_tool_use:{name}({id})>"),
@@ -381,49 +1087,534 @@ impl Display for Content {
 impl Content {
     /// Convenience function to construct a text content piece.
     pub fn from_text<S: Into<String>>(text: S) -> Self {
-        Content::Text { text: text.into() }
+        Content::Text {
+            text: text.into(),
+            citations: None,
+            cache_control: None,
+        }
+    }
+
+    /// Convenience function to construct an image content piece from raw image data.
+    ///
+    /// The data will be base64-encoded.
+    pub fn image_from_bytes<B: AsRef<[u8]>>(media_type: ImageMediaType, data: B) -> Self {
+        Content::Image {
+            source: ImageSource::Base64 {
+                media_type,
+                data: base64::engine::general_purpose::STANDARD.encode(data),
+            },
+            cache_control: None,
+        }
+    }
+
+    /// Convenience function to construct an image content piece referencing a URL.
+    pub fn image_from_url<S: Into<String>>(url: S) -> Self {
+        Content::Image {
+            source: ImageSource::Url { url: url.into() },
+            cache_control: None,
+        }
+    }
+
+    /// Convenience function to construct an image content piece referencing an uploaded file.
+    ///
+    /// Requests using it must enable [`Beta::FilesApi`].
+    pub fn image_from_file<S: Into<String>>(file_id: S) -> Self {
+        Content::Image {
+            source: ImageSource::File {
+                file_id: file_id.into(),
+            },
+            cache_control: None,
+        }
+    }
+
+    /// Convenience function to construct a document content piece.
+    pub fn document(document: Document) -> Self {
+        Content::Document(document)
+    }
+
+    /// Returns the cache breakpoint of the content piece, if any.
+    pub fn cache_control(&self) -> Option<&CacheControl> {
+        match self {
+            Content::Text { cache_control, .. } | Content::Image { cache_control, .. } => {
+                cache_control.as_ref()
+            }
+            Content::Document(Document { cache_control, .. })
+            | Content::ToolUse(ToolUse { cache_control, .. })
+            | Content::ToolResult(ToolResult { cache_control, .. }) => cache_control.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Sets or clears the cache breakpoint of the content piece.
+    ///
+    /// Returns `false` if the content type does not support cache breakpoints, in which case the
+    /// content is left unchanged.
+    pub fn set_cache_control(&mut self, value: Option<CacheControl>) -> bool {
+        match self {
+            Content::Text { cache_control, .. }
+            | Content::Image { cache_control, .. }
+            | Content::Document(Document { cache_control, .. })
+            | Content::ToolUse(ToolUse { cache_control, .. })
+            | Content::ToolResult(ToolResult { cache_control, .. }) => {
+                *cache_control = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets a cache breakpoint on the content piece, if supported.
+    ///
+    /// See [`Content::set_cache_control`].
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.set_cache_control(Some(cache_control));
+        self
+    }
+
+    /// Returns whether the content piece is a (possibly redacted) thinking block.
+    pub fn is_thinking(&self) -> bool {
+        matches!(
+            self,
+            Content::Thinking { .. } | Content::RedactedThinking { .. }
+        )
     }
 
     /// Returns the text content of the content piece, if it is a text piece.
     pub fn as_text(&self) -> Option<&str> {
         match self {
-            Content::Text { text } => Some(text.as_str()),
+            Content::Text { text, .. } => Some(text.as_str()),
             _ => None,
         }
     }
 }
 
+/// A page of a paginated list of objects.
+///
+/// Returned by list endpoints such as [`crate::batches::ListBatchesRequestBuilder`] and
+/// [`crate::models::ListModelsRequestBuilder`]. To fetch
+/// the next page, pass `last_id` as `after_id` to the next request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Page<T> {
+    /// The objects on this page.
+    pub data: Vec<T>,
+    /// Whether there are more objects beyond this page.
+    pub has_more: bool,
+    /// ID of the first object on this page, for fetching the previous page.
+    pub first_id: Option<String>,
+    /// ID of the last object on this page, for fetching the next page.
+    pub last_id: Option<String>,
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// Confirmation that an object has been deleted.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Deleted {
+    /// ID of the deleted object.
+    pub id: String,
+}
+
+/// Processing status of a message batch.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchProcessingStatus {
+    /// Requests are still being processed.
+    InProgress,
+    /// Cancellation has been initiated, processing requests are being canceled.
+    Canceling,
+    /// All requests have finished, results are available.
+    Ended,
+}
+
+/// Number of requests in a message batch, by status.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BatchRequestCounts {
+    /// Requests still being processed.
+    pub processing: u32,
+    /// Requests that completed successfully.
+    pub succeeded: u32,
+    /// Requests that resulted in an error.
+    pub errored: u32,
+    /// Requests canceled before processing.
+    pub canceled: u32,
+    /// Requests that expired before processing.
+    pub expired: u32,
+}
+
+/// A batch of messages requests, processed asynchronously.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/batch-processing> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MessageBatch {
+    /// Unique identifier of the batch.
+    pub id: String,
+    /// Processing status of the batch.
+    pub processing_status: BatchProcessingStatus,
+    /// Number of requests in the batch, by status.
+    pub request_counts: BatchRequestCounts,
+    /// RFC 3339 timestamp of the batch's creation.
+    pub created_at: String,
+    /// RFC 3339 timestamp after which unprocessed requests expire.
+    pub expires_at: String,
+    /// RFC 3339 timestamp of the end of processing, if ended.
+    pub ended_at: Option<String>,
+    /// RFC 3339 timestamp of the batch's archival, if archived.
+    pub archived_at: Option<String>,
+    /// RFC 3339 timestamp of the cancellation request, if canceled.
+    pub cancel_initiated_at: Option<String>,
+    /// URL of the results file, available once processing has ended.
+    pub results_url: Option<String>,
+}
+
+/// Information about a model available through the API.
+///
+/// See <https://docs.anthropic.com/en/docs/about-claude/models/overview> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModelInfo {
+    /// Unique model identifier, to be used in requests.
+    pub id: String,
+    /// Human-readable name of the model.
+    pub display_name: String,
+    /// RFC 3339 timestamp of the model's release.
+    pub created_at: String,
+}
+
+/// Metadata of a file uploaded through the Files API.
+///
+/// See <https://docs.anthropic.com/en/docs/build-with-claude/files> for details.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileMetadata {
+    /// Unique file identifier, used to reference the file in content blocks.
+    pub id: String,
+    /// Original name of the uploaded file.
+    pub filename: String,
+    /// MIME type of the file.
+    pub mime_type: String,
+    /// Size of the file in bytes.
+    pub size_bytes: u64,
+    /// RFC 3339 timestamp of the file's creation.
+    pub created_at: String,
+    /// Whether the file can be downloaded.
+    ///
+    /// Only files created by tools, not uploaded ones, can be downloaded.
+    #[serde(default)]
+    pub downloadable: bool,
+}
+
+/// An error response, as embedded in other responses.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// The error.
+    pub error: ApiError,
+}
+
+/// Result of a single request of a message batch.
+///
+/// Results are returned as JSON lines, see [`crate::batches::parse_results`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BatchResult {
+    /// The custom ID the request was submitted with.
+    pub custom_id: String,
+    /// The outcome of the request.
+    pub result: BatchOutcome,
+}
+
+/// Outcome of a single request of a message batch.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchOutcome {
+    /// The request succeeded.
+    Succeeded {
+        /// The response to the request.
+        message: MessagesResponse,
+    },
+    /// The request failed.
+    Errored {
+        /// The error returned for the request.
+        error: ErrorResponse,
+    },
+    /// The batch was canceled before the request was processed.
+    Canceled,
+    /// The batch expired before the request was processed.
+    Expired,
+}
+
 /// Anthropic API error.
 ///
 /// Errors defined in the Anthropic API specification, do not include parsing or transport errors.
+/// Every error carries the human-readable message returned by the API and, if the error was
+/// returned as a response, the ID of the request (useful when contacting support).
 #[derive(Clone, Debug, thiserror::Error, Deserialize, Serialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
+#[serde(from = "RawApiError", into = "RawApiError")]
 pub enum ApiError {
     /// HTTP 400, invalid request
-    #[error("Invalid request")]
-    InvalidRequestError,
+    #[error("Invalid request: {message}")]
+    InvalidRequestError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 401, authentication error
-    #[error("Authentication error")]
-    AuthenticationError,
+    #[error("Authentication error: {message}")]
+    AuthenticationError {
+        message: String,
+        request_id: Option<String>,
+    },
+    /// HTTP 402, there is an issue with your account's billing
+    #[error("Billing error: {message}")]
+    BillingError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 403, your API key does not have permission to use the specified resource
-    #[error("Permission error")]
-    PermissionError,
+    #[error("Permission error: {message}")]
+    PermissionError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 404, the requested resource was not found
-    #[error("Not found")]
-    NotFoundError,
+    #[error("Not found: {message}")]
+    NotFoundError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 413, request exceeds the maximum allowed number of bytes
-    #[error("Request too large")]
-    RequestTooLarge,
+    #[error("Request too large: {message}")]
+    RequestTooLarge {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 429, your account has hit a rate limit
-    #[error("Rate limit exceeded")]
-    RateLimitError,
+    #[error("Rate limit exceeded: {message}")]
+    RateLimitError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 500, an unexpected error has occurred internal to Anthropic's systems
-    #[error("API error")]
+    #[error("API error: {message}")]
     #[allow(clippy::enum_variant_names)]
-    ApiError,
+    ApiError {
+        message: String,
+        request_id: Option<String>,
+    },
+    /// HTTP 504, the request timed out while processing
+    #[error("Request timed out: {message}")]
+    TimeoutError {
+        message: String,
+        request_id: Option<String>,
+    },
     /// HTTP 529, Anthropic's API is temporarily overloaded
-    #[error("API overloaded")]
-    OverloadedError,
+    #[error("API overloaded: {message}")]
+    OverloadedError {
+        message: String,
+        request_id: Option<String>,
+    },
+    /// An error type unknown to this crate.
+    #[error("API error of type {error_type}: {message}")]
+    Unknown {
+        error_type: String,
+        message: String,
+        request_id: Option<String>,
+    },
+}
+
+impl ApiError {
+    /// Returns the type of the error, e.g. `invalid_request_error`.
+    pub fn error_type(&self) -> &str {
+        match self {
+            ApiError::InvalidRequestError { .. } => "invalid_request_error",
+            ApiError::AuthenticationError { .. } => "authentication_error",
+            ApiError::BillingError { .. } => "billing_error",
+            ApiError::PermissionError { .. } => "permission_error",
+            ApiError::NotFoundError { .. } => "not_found_error",
+            ApiError::RequestTooLarge { .. } => "request_too_large",
+            ApiError::RateLimitError { .. } => "rate_limit_error",
+            ApiError::ApiError { .. } => "api_error",
+            ApiError::TimeoutError { .. } => "timeout_error",
+            ApiError::OverloadedError { .. } => "overloaded_error",
+            ApiError::Unknown { error_type, .. } => error_type,
+        }
+    }
+
+    /// Returns whether the request may succeed when retried.
+    ///
+    /// This is the case for rate limiting, overloading, timeouts and internal errors.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ApiError::RateLimitError { .. }
+                | ApiError::ApiError { .. }
+                | ApiError::TimeoutError { .. }
+                | ApiError::OverloadedError { .. }
+        )
+    }
+
+    /// Returns the error message returned by the API.
+    pub fn message(&self) -> &str {
+        self.parts().0
+    }
+
+    /// Returns the ID of the failed request, if known.
+    pub fn request_id(&self) -> Option<&str> {
+        self.parts().1.as_deref()
+    }
+
+    /// Sets the ID of the failed request.
+    pub fn set_request_id(&mut self, id: Option<String>) {
+        *self.request_id_mut() = id;
+    }
+
+    /// Returns the message and request ID.
+    fn parts(&self) -> (&str, &Option<String>) {
+        match self {
+            ApiError::InvalidRequestError {
+                message,
+                request_id,
+            }
+            | ApiError::AuthenticationError {
+                message,
+                request_id,
+            }
+            | ApiError::BillingError {
+                message,
+                request_id,
+            }
+            | ApiError::PermissionError {
+                message,
+                request_id,
+            }
+            | ApiError::NotFoundError {
+                message,
+                request_id,
+            }
+            | ApiError::RequestTooLarge {
+                message,
+                request_id,
+            }
+            | ApiError::RateLimitError {
+                message,
+                request_id,
+            }
+            | ApiError::ApiError {
+                message,
+                request_id,
+            }
+            | ApiError::TimeoutError {
+                message,
+                request_id,
+            }
+            | ApiError::OverloadedError {
+                message,
+                request_id,
+            }
+            | ApiError::Unknown {
+                message,
+                request_id,
+                ..
+            } => (message, request_id),
+        }
+    }
+
+    /// Returns a mutable reference to the request ID.
+    fn request_id_mut(&mut self) -> &mut Option<String> {
+        match self {
+            ApiError::InvalidRequestError { request_id, .. }
+            | ApiError::AuthenticationError { request_id, .. }
+            | ApiError::BillingError { request_id, .. }
+            | ApiError::PermissionError { request_id, .. }
+            | ApiError::NotFoundError { request_id, .. }
+            | ApiError::RequestTooLarge { request_id, .. }
+            | ApiError::RateLimitError { request_id, .. }
+            | ApiError::ApiError { request_id, .. }
+            | ApiError::TimeoutError { request_id, .. }
+            | ApiError::OverloadedError { request_id, .. }
+            | ApiError::Unknown { request_id, .. } => request_id,
+        }
+    }
+}
+
+/// Wire representation of an [`ApiError`].
+///
+/// The request ID is not part of the error object, but of the surrounding response.
+#[derive(Deserialize, Serialize)]
+struct RawApiError {
+    #[serde(rename = "type")]
+    error_type: String,
+    #[serde(default)]
+    message: String,
+}
+
+impl From<RawApiError> for ApiError {
+    fn from(raw: RawApiError) -> Self {
+        let RawApiError {
+            error_type,
+            message,
+        } = raw;
+        let request_id = None;
+
+        match error_type.as_str() {
+            "invalid_request_error" => ApiError::InvalidRequestError {
+                message,
+                request_id,
+            },
+            "authentication_error" => ApiError::AuthenticationError {
+                message,
+                request_id,
+            },
+            "billing_error" => ApiError::BillingError {
+                message,
+                request_id,
+            },
+            "permission_error" => ApiError::PermissionError {
+                message,
+                request_id,
+            },
+            "not_found_error" => ApiError::NotFoundError {
+                message,
+                request_id,
+            },
+            "request_too_large" => ApiError::RequestTooLarge {
+                message,
+                request_id,
+            },
+            "rate_limit_error" => ApiError::RateLimitError {
+                message,
+                request_id,
+            },
+            "api_error" => ApiError::ApiError {
+                message,
+                request_id,
+            },
+            "timeout_error" => ApiError::TimeoutError {
+                message,
+                request_id,
+            },
+            "overloaded_error" => ApiError::OverloadedError {
+                message,
+                request_id,
+            },
+            _ => ApiError::Unknown {
+                error_type,
+                message,
+                request_id,
+            },
+        }
+    }
+}
+
+impl From<ApiError> for RawApiError {
+    fn from(error: ApiError) -> Self {
+        RawApiError {
+            error_type: error.error_type().to_owned(),
+            message: error.message().to_owned(),
+        }
+    }
 }
 
 /// A response from the Anthropic API.
@@ -435,7 +1626,33 @@ pub enum ApiResponse {
     /// A response to a messages request.
     Message(MessagesResponse),
     /// An error response from the API.
-    Error { error: ApiError },
+    Error {
+        error: ApiError,
+        /// ID of the failed request.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+    },
+    /// A message batch.
+    MessageBatch(MessageBatch),
+    /// Confirmation of a deleted message batch.
+    MessageBatchDeleted(Deleted),
+    /// Information about a model.
+    Model(ModelInfo),
+    /// Metadata of an uploaded file.
+    File(FileMetadata),
+    /// Confirmation of a deleted file.
+    FileDeleted(Deleted),
+    /// A response to a token counting request.
+    ///
+    /// This response carries no `type` tag.
+    #[serde(untagged)]
+    TokenCount(TokenCount),
+    /// A page of a list response, such as [`Page<MessageBatch>`].
+    ///
+    /// This response carries no `type` tag, its items are converted into their actual type when
+    /// converting the response.
+    #[serde(untagged)]
+    Page(Page<Value>),
 }
 
 impl ApiResponse {
@@ -444,6 +1661,13 @@ impl ApiResponse {
         match self {
             ApiResponse::Message(_) => "message",
             ApiResponse::Error { .. } => "error",
+            ApiResponse::MessageBatch(_) => "message_batch",
+            ApiResponse::MessageBatchDeleted(_) => "message_batch_deleted",
+            ApiResponse::Model(_) => "model",
+            ApiResponse::File(_) => "file",
+            ApiResponse::FileDeleted(_) => "file_deleted",
+            ApiResponse::TokenCount(_) => "token_count",
+            ApiResponse::Page(_) => "page",
         }
     }
 }
@@ -454,7 +1678,90 @@ impl TryFrom<ApiResponse> for MessagesResponse {
     fn try_from(helper: ApiResponse) -> Result<Self, Self::Error> {
         match helper {
             ApiResponse::Message(response) => Ok(response),
-            ApiResponse::Error { error: _ } => Err(()),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<ApiResponse> for MessageBatch {
+    type Error = ();
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        match response {
+            ApiResponse::MessageBatch(batch) => Ok(batch),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<ApiResponse> for ModelInfo {
+    type Error = ();
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        match response {
+            ApiResponse::Model(model) => Ok(model),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<ApiResponse> for FileMetadata {
+    type Error = ();
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        match response {
+            ApiResponse::File(file) => Ok(file),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<ApiResponse> for Deleted {
+    type Error = ();
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        match response {
+            ApiResponse::MessageBatchDeleted(deleted) | ApiResponse::FileDeleted(deleted) => {
+                Ok(deleted)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl<T> TryFrom<ApiResponse> for Page<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Error = crate::ConversionError;
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        let ApiResponse::Page(page) = response else {
+            return Err(crate::ConversionError::UnexpectedType);
+        };
+
+        let data = page
+            .data
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Page {
+            data,
+            has_more: page.has_more,
+            first_id: page.first_id,
+            last_id: page.last_id,
+        })
+    }
+}
+
+impl TryFrom<ApiResponse> for TokenCount {
+    type Error = ();
+
+    fn try_from(response: ApiResponse) -> Result<Self, Self::Error> {
+        match response {
+            ApiResponse::TokenCount(count) => Ok(count),
+            _ => Err(()),
         }
     }
 }
@@ -507,7 +1814,7 @@ pub struct MessagesResponse {
 /// prefixes, reducing both latency and costs for repeated prompts.
 ///
 /// See <https://docs.anthropic.com/en/docs/build-with-claude/prompt-caching> for details.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Usage {
     /// Tokens sent to the model that were not served from cache.
     pub input_tokens: u32,
@@ -530,6 +1837,25 @@ pub struct Usage {
     pub cache_read_input_tokens: u32,
 }
 
+impl Usage {
+    /// Returns the total number of input tokens, whether cached or not.
+    pub fn total_input_tokens(&self) -> u32 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+
+    /// Returns the fraction of input tokens that were served from the cache.
+    ///
+    /// Ranges from `0.0` (no cache hits) to `1.0` (entire prompt read from cache). Returns `0.0`
+    /// if there were no input tokens at all.
+    pub fn cache_hit_ratio(&self) -> f64 {
+        let total = self.total_input_tokens();
+        if total == 0 {
+            return 0.0;
+        }
+        f64::from(self.cache_read_input_tokens) / f64::from(total)
+    }
+}
+
 /// Usage statistics for server-side tools.
 ///
 /// Server-side tools (like web search) run on Anthropic's infrastructure. This struct tracks
@@ -661,6 +1987,7 @@ pub enum Delta {
     TextDelta { text: String },
     InputJsonDelta { partial_json: String },
     ThinkingDelta { thinking: String },
+    CitationsDelta { citation: Citation },
     SignatureDelta { signature: String },
 }
 
@@ -704,7 +2031,10 @@ pub struct MessageDelta {
 
 #[cfg(test)]
 mod tests {
-    use super::{Content, Delta, StopReason, StreamEvent, Usage};
+    use super::{
+        CacheControl, CacheTtl, Citation, Content, Delta, Document, DocumentSource, ImageMediaType,
+        ImageSource, StopReason, StreamEvent, SystemPrompt, TextBlock, Tool, Usage,
+    };
 
     #[test]
     fn test_deserialize_content_block_delta_text() {
@@ -783,6 +2113,18 @@ mod tests {
         assert_eq!(usage.cache_read_input_tokens, 500);
     }
 
+    #[test]
+    fn test_usage_cache_hit_ratio() {
+        let data = br#"{"input_tokens": 50, "output_tokens": 50, "cache_creation_input_tokens": 50, "cache_read_input_tokens": 300}"#;
+        let usage: Usage = serde_json::from_slice(data).expect("should deserialize");
+        assert_eq!(usage.total_input_tokens(), 400);
+        assert_eq!(usage.cache_hit_ratio(), 0.75);
+
+        let empty = br#"{"input_tokens": 0, "output_tokens": 0}"#;
+        let usage: Usage = serde_json::from_slice(empty).expect("should deserialize");
+        assert_eq!(usage.cache_hit_ratio(), 0.0);
+    }
+
     #[test]
     fn test_usage_without_cache_fields() {
         let data = br#"{"input_tokens": 100, "output_tokens": 50}"#;
@@ -843,4 +2185,196 @@ mod tests {
             _ => panic!("expected ContentBlockStart with Unknown"),
         }
     }
+
+    #[test]
+    fn test_image_content_round_trip() {
+        let content = Content::image_from_bytes(ImageMediaType::Png, b"\x89PNG");
+        let serialized = serde_json::to_value(&content).expect("should serialize");
+        assert_eq!(serialized["type"], "image");
+        assert_eq!(serialized["source"]["type"], "base64");
+        assert_eq!(serialized["source"]["media_type"], "image/png");
+        assert_eq!(serialized["source"]["data"], "iVBORw==");
+
+        let deserialized: Content = serde_json::from_value(serialized).expect("should deserialize");
+        match deserialized {
+            Content::Image {
+                source: ImageSource::Base64 { media_type, data },
+                ..
+            } => {
+                assert_eq!(media_type, ImageMediaType::Png);
+                assert_eq!(data, "iVBORw==");
+            }
+            other => panic!("expected base64 image, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_image_url_content() {
+        let data = br#"{"type": "image", "source": {"type": "url", "url": "https://example.com/cat.jpg"}}"#;
+        let content: Content = serde_json::from_slice(data).expect("should deserialize");
+        match &content {
+            Content::Image {
+                source: ImageSource::Url { url },
+                ..
+            } => assert_eq!(url, "https://example.com/cat.jpg"),
+            other => panic!("expected url image, got {:?}", other),
+        }
+        assert_eq!(
+            serde_json::to_value(&content).expect("should serialize"),
+            serde_json::to_value(Content::image_from_url("https://example.com/cat.jpg")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_document_serialization() {
+        let content = Content::document(
+            Document::from_text("The grass is green.")
+                .title("Facts")
+                .citations(true),
+        );
+        let serialized = serde_json::to_value(&content).expect("should serialize");
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "type": "document",
+                "source": {"type": "text", "media_type": "text/plain", "data": "The grass is green."},
+                "title": "Facts",
+                "citations": {"enabled": true}
+            })
+        );
+
+        let pdf = serde_json::to_value(Content::document(Document::from_pdf_bytes(b"%PDF")))
+            .expect("should serialize");
+        assert_eq!(pdf["source"]["media_type"], "application/pdf");
+        assert_eq!(pdf["source"]["data"], "JVBERg==");
+        assert!(pdf.get("citations").is_none());
+
+        let deserialized: Content = serde_json::from_value(serialized).expect("should deserialize");
+        let Content::Document(document) = deserialized else {
+            panic!("expected document");
+        };
+        assert!(matches!(document.source, DocumentSource::Text { .. }));
+        assert_eq!(document.title.as_deref(), Some("Facts"));
+        assert!(document.citations.unwrap().enabled);
+    }
+
+    #[test]
+    fn test_deserialize_text_with_citations() {
+        let data = br#"{"type": "text", "text": "the grass is green", "citations": [
+            {"type": "char_location", "cited_text": "The grass is green.", "document_index": 0, "document_title": "Facts", "start_char_index": 0, "end_char_index": 20},
+            {"type": "page_location", "cited_text": "Page text", "document_index": 1, "document_title": null, "start_page_number": 1, "end_page_number": 2},
+            {"type": "content_block_location", "cited_text": "Block", "document_index": 2, "document_title": null, "start_block_index": 0, "end_block_index": 1}
+        ]}"#;
+        let content: Content = serde_json::from_slice(data).expect("should deserialize");
+        let Content::Text {
+            text, citations, ..
+        } = content
+        else {
+            panic!("expected text");
+        };
+        assert_eq!(text, "the grass is green");
+        let citations = citations.expect("should have citations");
+        assert_eq!(citations.len(), 3);
+        assert!(matches!(
+            citations[0],
+            Citation::CharLocation {
+                start_char_index: 0,
+                end_char_index: 20,
+                ..
+            }
+        ));
+        assert!(matches!(
+            citations[1],
+            Citation::PageLocation {
+                start_page_number: 1,
+                ..
+            }
+        ));
+        assert_eq!(citations[2].cited_text(), Some("Block"));
+
+        // Text without citations must not emit a `citations` key.
+        let plain = serde_json::to_value(Content::from_text("hi")).expect("should serialize");
+        assert!(plain.get("citations").is_none());
+    }
+
+    #[test]
+    fn test_thinking_content_round_trip() {
+        let data = br#"[{"type": "thinking", "thinking": "Let me think.", "signature": "sig_abc"}, {"type": "redacted_thinking", "data": "EmwKAhgBEgy"}]"#;
+        let contents: Vec<Content> = serde_json::from_slice(data).expect("should deserialize");
+
+        assert!(matches!(
+            &contents[0],
+            Content::Thinking { thinking, signature }
+                if thinking == "Let me think." && signature == "sig_abc"
+        ));
+        assert!(matches!(
+            &contents[1],
+            Content::RedactedThinking { data } if data == "EmwKAhgBEgy"
+        ));
+        assert!(contents.iter().all(Content::is_thinking));
+
+        let reserialized = serde_json::to_value(&contents).expect("should serialize");
+        let original: serde_json::Value = serde_json::from_slice(data).unwrap();
+        assert_eq!(reserialized, original);
+    }
+
+    #[test]
+    fn test_cache_control_serialization() {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Input {
+            value: String,
+        }
+
+        let tool = Tool::new::<Input, _, _>("tool", "A tool.")
+            .with_cache_control(CacheControl::ephemeral_with_ttl(CacheTtl::OneHour));
+        let serialized = serde_json::to_value(&tool).expect("should serialize");
+        assert_eq!(
+            serialized["cache_control"],
+            serde_json::json!({"type": "ephemeral", "ttl": "1h"})
+        );
+
+        let mut content =
+            Content::from_text("cached").with_cache_control(CacheControl::ephemeral());
+        assert_eq!(
+            serde_json::to_value(&content).expect("should serialize"),
+            serde_json::json!({"type": "text", "text": "cached", "cache_control": {"type": "ephemeral"}})
+        );
+        assert!(content.set_cache_control(None));
+        assert!(content.cache_control().is_none());
+
+        let mut thinking = Content::RedactedThinking {
+            data: "abc".to_string(),
+        };
+        assert!(!thinking.set_cache_control(Some(CacheControl::ephemeral())));
+    }
+
+    #[test]
+    fn test_system_prompt_serialization() {
+        let plain = SystemPrompt::from("Be helpful.");
+        assert_eq!(
+            serde_json::to_value(&plain).expect("should serialize"),
+            serde_json::json!("Be helpful.")
+        );
+
+        let cached = plain.with_cache_control(CacheControl::ephemeral());
+        assert_eq!(
+            serde_json::to_value(&cached).expect("should serialize"),
+            serde_json::json!([{"type": "text", "text": "Be helpful.", "cache_control": {"type": "ephemeral"}}])
+        );
+
+        let blocks = SystemPrompt::from(vec![
+            TextBlock::new("First."),
+            TextBlock::new("Second.").with_cache_control(CacheControl::ephemeral()),
+        ]);
+        assert_eq!(blocks.to_string(), "First.\nSecond.");
+
+        let round_tripped: SystemPrompt =
+            serde_json::from_value(serde_json::to_value(&blocks).unwrap())
+                .expect("should deserialize");
+        let SystemPrompt::Blocks(blocks) = round_tripped else {
+            panic!("expected blocks");
+        };
+        assert_eq!(blocks[1].cache_control, Some(CacheControl::ephemeral()));
+    }
 }
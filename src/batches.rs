@@ -0,0 +1,359 @@
+//! Message Batches API.
+//!
+//! Message batches allow submitting a large number of messages requests at once, which are then
+//! processed asynchronously. See
+//! <https://docs.anthropic.com/en/docs/build-with-claude/batch-processing> for details.
+//!
+//! The typical lifecycle of a batch is:
+//!
+//! 1. Create the batch using [`CreateBatchRequestBuilder`], the API responds with a
+//!    [`MessageBatch`](crate::anthropic::MessageBatch).
+//! 2. Poll the batch using [`retrieve`] until its
+//!    [`processing_status`](crate::anthropic::MessageBatch::processing_status) is
+//!    [`Ended`](crate::anthropic::BatchProcessingStatus::Ended).
+//! 3. Fetch the results using [`results`] and parse them using [`parse_results`].
+//!
+//! ## Example
+//!
+//! ```
+//! use claus::{Api, MessagesRequestBuilder, anthropic::Role, batches::CreateBatchRequestBuilder};
+//!
+//! let api = Api::new("sk-ant-api03-...");
+//!
+//! let http_req = CreateBatchRequestBuilder::new()
+//!     .push("first", MessagesRequestBuilder::new().push_message(Role::User, "Hello!"))
+//!     .push("second", MessagesRequestBuilder::new().push_message(Role::User, "Hi!"))
+//!     .build(&api);
+//!
+//! assert_eq!(http_req.path, "/v1/messages/batches");
+//! ```
+
+use serde::Serialize;
+
+use crate::{
+    Api, ListRequestBuilder, Listable, MessagesRequestBuilder,
+    anthropic::{BatchResult, MessageBatch, MessagesBody},
+    http_request::{Body, HttpRequest, path_segment},
+};
+
+/// Path of the message batches endpoint.
+const BATCHES_PATH: &str = "/v1/messages/batches";
+
+/// Builder for a request creating a new message batch.
+///
+/// Every request in the batch is identified by a custom ID, which must be unique within the
+/// batch. Once sent, you should expect to receive a [`MessageBatch`](crate::anthropic::MessageBatch) from the API.
+#[derive(Clone, Debug, Default)]
+pub struct CreateBatchRequestBuilder {
+    /// The requests in the batch, along with their custom IDs.
+    requests: Vec<(String, MessagesRequestBuilder)>,
+}
+
+impl CreateBatchRequestBuilder {
+    /// Creates a new, empty batch request builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a messages request to the batch.
+    ///
+    /// The request is built using the defaults of the [`Api`] passed to
+    /// [`CreateBatchRequestBuilder::build`]. Streaming is not supported for batched requests, the
+    /// [`stream`](MessagesRequestBuilder::stream) setting is ignored. Since the batch is sent as a
+    /// single HTTP request, betas and extra headers of `request` are ignored as well; set them on
+    /// the [`Api`] instead.
+    pub fn push<S: Into<String>>(mut self, custom_id: S, request: MessagesRequestBuilder) -> Self {
+        self.requests.push((custom_id.into(), request));
+        self
+    }
+
+    /// Returns the number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns whether the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Builds an HTTP request for creating the batch.
+    pub fn build(&self, api: &Api) -> HttpRequest {
+        let body = CreateBatchBody {
+            requests: self
+                .requests
+                .iter()
+                .map(|(custom_id, request)| BatchRequestBody {
+                    custom_id,
+                    params: MessagesBody {
+                        stream: false,
+                        ..request.body(api)
+                    },
+                })
+                .collect(),
+        };
+
+        api.create_request(
+            "POST",
+            BATCHES_PATH,
+            serde_json::to_string(&body).expect("failed to serialize batch"),
+        )
+    }
+}
+
+/// Body of a batch creation request.
+#[derive(Debug, Serialize)]
+struct CreateBatchBody<'a> {
+    requests: Vec<BatchRequestBody<'a>>,
+}
+
+/// A single request within a batch creation request.
+#[derive(Debug, Serialize)]
+struct BatchRequestBody<'a> {
+    custom_id: &'a str,
+    params: MessagesBody<'a>,
+}
+
+/// Builder for a request listing message batches.
+///
+/// Batches are returned most recent first. Once sent, you should expect to receive a
+/// [`Page<MessageBatch>`](crate::anthropic::Page) from the API.
+pub type ListBatchesRequestBuilder = ListRequestBuilder<MessageBatch>;
+
+impl Listable for MessageBatch {
+    fn list_request(api: &Api) -> HttpRequest {
+        api.create_request("GET", BATCHES_PATH, Body::Empty)
+    }
+}
+
+/// Builds an HTTP request retrieving a message batch.
+///
+/// Once sent, you should expect to receive a [`MessageBatch`](crate::anthropic::MessageBatch) from the API.
+///
+/// # Panics
+///
+/// Panics if `batch_id` is empty, `.` or `..`.
+pub fn retrieve(api: &Api, batch_id: &str) -> HttpRequest {
+    api.create_request(
+        "GET",
+        format!("{BATCHES_PATH}/{}", path_segment(batch_id)),
+        Body::Empty,
+    )
+}
+
+/// Builds an HTTP request canceling a message batch.
+///
+/// Requests that have not been processed yet will be canceled, the batch's status changes to
+/// [`Canceling`](crate::anthropic::BatchProcessingStatus::Canceling). Once sent, you should
+/// expect to receive a [`MessageBatch`](crate::anthropic::MessageBatch) from the API.
+///
+/// # Panics
+///
+/// Panics if `batch_id` is empty, `.` or `..`.
+pub fn cancel(api: &Api, batch_id: &str) -> HttpRequest {
+    api.create_request(
+        "POST",
+        format!("{BATCHES_PATH}/{}/cancel", path_segment(batch_id)),
+        Body::Empty,
+    )
+}
+
+/// Builds an HTTP request deleting a message batch.
+///
+/// Only batches that have finished processing can be deleted. Once sent, you should expect to
+/// receive a [`Deleted`](crate::anthropic::Deleted) from the API.
+///
+/// # Panics
+///
+/// Panics if `batch_id` is empty, `.` or `..`.
+pub fn delete(api: &Api, batch_id: &str) -> HttpRequest {
+    api.create_request(
+        "DELETE",
+        format!("{BATCHES_PATH}/{}", path_segment(batch_id)),
+        Body::Empty,
+    )
+}
+
+/// Builds an HTTP request fetching the results of a message batch.
+///
+/// Results are only available once the batch has ended. The response is not JSON, but JSON lines,
+/// which must be parsed using [`parse_results`].
+///
+/// # Panics
+///
+/// Panics if `batch_id` is empty, `.` or `..`.
+pub fn results(api: &Api, batch_id: &str) -> HttpRequest {
+    api.create_request(
+        "GET",
+        format!("{BATCHES_PATH}/{}/results", path_segment(batch_id)),
+        Body::Empty,
+    )
+}
+
+/// Parses the JSON lines returned by the batch results endpoint.
+///
+/// Each line is parsed into a [`BatchResult`], empty lines are skipped. Results are not
+/// guaranteed to be in the same order as the requests, use
+/// [`custom_id`](BatchResult::custom_id) to match them.
+pub fn parse_results(
+    jsonl: &str,
+) -> impl Iterator<Item = Result<BatchResult, serde_json::Error>> + '_ {
+    jsonl
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{CreateBatchRequestBuilder, ListBatchesRequestBuilder, parse_results};
+    use crate::{
+        Api, MessagesRequestBuilder, ResponseError,
+        anthropic::{
+            ApiError, BatchOutcome, BatchProcessingStatus, Deleted, MessageBatch, Page, Role,
+        },
+        deserialize_response,
+    };
+
+    #[test]
+    fn test_create_batch() {
+        let api = Api::new("test-key").default_max_tokens(512);
+
+        let http_req = CreateBatchRequestBuilder::new()
+            .push(
+                "first",
+                MessagesRequestBuilder::new().push_message(Role::User, "Hello"),
+            )
+            .push(
+                "second",
+                MessagesRequestBuilder::new()
+                    .max_tokens(64)
+                    .stream(true)
+                    .push_message(Role::User, "Bye"),
+            )
+            .build(&api);
+
+        assert_eq!(http_req.method, "POST");
+        assert_eq!(http_req.path, "/v1/messages/batches");
+
+        let body: Value = serde_json::from_str(http_req.body.as_json().unwrap()).unwrap();
+        let requests = body["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["custom_id"], "first");
+        assert_eq!(requests[0]["params"]["max_tokens"], 512);
+        assert_eq!(requests[0]["params"]["messages"][0]["role"], "user");
+        assert_eq!(requests[1]["custom_id"], "second");
+        assert_eq!(requests[1]["params"]["max_tokens"], 64);
+        assert!(requests[1]["params"].get("stream").is_none());
+    }
+
+    #[test]
+    fn test_batch_management_requests() {
+        let api = Api::new("test-key");
+
+        let http_req = ListBatchesRequestBuilder::new()
+            .after_id("msgbatch_01")
+            .limit(10)
+            .build(&api);
+        assert_eq!(http_req.method, "GET");
+        assert_eq!(http_req.path, "/v1/messages/batches");
+        assert_eq!(
+            http_req.path_and_query(),
+            "/v1/messages/batches?after_id=msgbatch_01&limit=10"
+        );
+        assert!(http_req.body.is_empty());
+
+        assert_eq!(
+            super::retrieve(&api, "msgbatch_01").path,
+            "/v1/messages/batches/msgbatch_01"
+        );
+        assert_eq!(
+            super::cancel(&api, "msgbatch_01").path,
+            "/v1/messages/batches/msgbatch_01/cancel"
+        );
+        assert_eq!(super::delete(&api, "msgbatch_01").method, "DELETE");
+        assert_eq!(
+            super::results(&api, "msgbatch_01").path,
+            "/v1/messages/batches/msgbatch_01/results"
+        );
+
+        // IDs cannot change the endpoint.
+        assert_eq!(
+            super::cancel(&api, "../files?x").path,
+            "/v1/messages/batches/..%2Ffiles%3Fx/cancel"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_dot_segment_batch_id() {
+        super::retrieve(&Api::new("test-key"), "..");
+    }
+
+    #[test]
+    fn test_deserialize_batch_responses() {
+        let batch: MessageBatch = deserialize_response(
+            r#"{
+                "id": "msgbatch_01",
+                "type": "message_batch",
+                "processing_status": "in_progress",
+                "request_counts": {"processing": 2, "succeeded": 0, "errored": 0, "canceled": 0, "expired": 0},
+                "ended_at": null,
+                "created_at": "2024-08-20T18:37:24.100435Z",
+                "expires_at": "2024-08-21T18:37:24.100435Z",
+                "archived_at": null,
+                "cancel_initiated_at": null,
+                "results_url": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(batch.processing_status, BatchProcessingStatus::InProgress);
+        assert_eq!(batch.request_counts.processing, 2);
+
+        let page: Page<MessageBatch> = deserialize_response(
+            r#"{"data": [], "has_more": false, "first_id": null, "last_id": null}"#,
+        )
+        .unwrap();
+        assert!(page.data.is_empty());
+
+        // Invalid items are reported as such, not as a page of the wrong type.
+        let result: Result<Page<MessageBatch>, _> = deserialize_response(
+            r#"{"data": [{"id": "msgbatch_01"}], "has_more": false, "first_id": null, "last_id": null}"#,
+        );
+        assert!(matches!(result, Err(ResponseError::Serde(_))));
+
+        let deleted: Deleted =
+            deserialize_response(r#"{"id": "msgbatch_01", "type": "message_batch_deleted"}"#)
+                .unwrap();
+        assert_eq!(deleted.id, "msgbatch_01");
+    }
+
+    #[test]
+    fn test_parse_results() {
+        let jsonl = concat!(
+            r#"{"custom_id":"first","result":{"type":"succeeded","message":{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-20250514","content":[{"type":"text","text":"Hi!"}],"stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":3}}}}"#,
+            "\n",
+            r#"{"custom_id":"second","result":{"type":"errored","error":{"type":"error","error":{"type":"invalid_request_error","message":"max_tokens too large"}}}}"#,
+            "\n",
+            r#"{"custom_id":"third","result":{"type":"expired"}}"#,
+            "\n",
+        );
+
+        let results: Vec<_> = parse_results(jsonl).collect::<Result<_, _>>().unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].custom_id, "first");
+        let BatchOutcome::Succeeded { ref message } = results[0].result else {
+            panic!("expected success, got {:?}", results[0].result);
+        };
+        assert_eq!(message.message.content.len(), 1);
+
+        assert!(matches!(
+            results[1].result,
+            BatchOutcome::Errored { ref error } if matches!(error.error, ApiError::InvalidRequestError { ref message, .. } if message == "max_tokens too large")
+        ));
+        assert!(matches!(results[2].result, BatchOutcome::Expired));
+    }
+}
@@ -0,0 +1,573 @@
+//! Optional I/O layer.
+//!
+//! The rest of this crate only builds [`HttpRequest`]s and parses [`HttpResponse`]s, leaving the
+//! actual sending to the caller. For the common case of simply sending a request and waiting for
+//! its response, this module offers a [`Client`] (or [`AsyncClient`]) that combines an [`Api`]
+//! with a [`Transport`] (or [`AsyncTransport`]), applying a [`RetryPolicy`] along the way.
+//!
+//! Streaming responses are not supported, use the sans-IO interface for those instead. Sending
+//! through a [`Conversation`] with streaming enabled results in an error.
+//!
+//! ## Features
+//!
+//! If the `reqwest-blocking` feature is enabled, [`Transport`] is implemented for
+//! [`reqwest::blocking::Client`]. If the `reqwest` feature is enabled, [`AsyncTransport`] is
+//! implemented for [`reqwest::Client`], using `tokio` for waiting between retries.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "reqwest-blocking")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use claus::{Api, MessagesRequestBuilder, anthropic::Role, client::Client};
+//!
+//! let client = Client::new(Api::new("sk-ant-api03-..."), reqwest::blocking::Client::new());
+//!
+//! let request = MessagesRequestBuilder::new().push_message(Role::User, "Hello, Claude!");
+//! let response = client.messages(&request)?;
+//! println!("{:?}", response.message.content);
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "reqwest-blocking"))]
+//! # fn main() {}
+//! ```
+
+use std::{future::Future, time::Duration};
+
+use crate::{
+    Api, ConversionError, MessagesRequestBuilder, ResponseError,
+    anthropic::{self, ApiResponse},
+    conversation::{Action, Conversation},
+    http_request::HttpRequest,
+    http_response::HttpResponse,
+    parse_response,
+    retry::RetryPolicy,
+};
+
+/// A blocking HTTP transport.
+///
+/// Sends a single request, without any retries. Non-success responses must be returned as
+/// responses, not as errors.
+pub trait Transport {
+    /// Error returned if the request could not be sent or no response was received.
+    type Error;
+
+    /// Sends a request and returns the complete response.
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error>;
+}
+
+/// An asynchronous HTTP transport.
+///
+/// See [`Transport`] for the expected behavior. Since waiting depends on the async runtime, the
+/// transport is also responsible for sleeping between retries.
+pub trait AsyncTransport {
+    /// Error returned if the request could not be sent or no response was received.
+    type Error;
+
+    /// Sends a request and returns the complete response.
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse, Self::Error>> + Send;
+
+    /// Waits for the given duration.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// Error returned by a [`Client`] or [`AsyncClient`].
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError<E> {
+    /// The transport failed to send the request.
+    #[error("Transport error: {0}")]
+    Transport(#[source] E),
+    /// A response was received, but it is an error or could not be parsed.
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+    /// The conversation has streaming enabled, which is not supported by the client.
+    #[error("Streaming conversations are not supported")]
+    StreamingConversation,
+}
+
+/// A blocking client sending requests through a [`Transport`].
+///
+/// Responses that the [`RetryPolicy`] deems temporary failures are retried. Transport errors are
+/// returned immediately, since it is unknown whether the request reached the API.
+#[derive(Clone, Debug)]
+pub struct Client<T> {
+    api: Api,
+    transport: T,
+    retry_policy: RetryPolicy,
+}
+
+/// An asynchronous client sending requests through an [`AsyncTransport`].
+///
+/// Behaves like [`Client`], see its documentation for details.
+#[derive(Clone, Debug)]
+pub struct AsyncClient<T> {
+    api: Api,
+    transport: T,
+    retry_policy: RetryPolicy,
+}
+
+impl<T> Client<T> {
+    /// Creates a new client with the default [`RetryPolicy`].
+    pub fn new(api: Api, transport: T) -> Self {
+        Self {
+            api,
+            transport,
+            retry_policy: RetryPolicy::new(),
+        }
+    }
+
+    /// Sets the retry policy.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Returns the [`Api`] used to build requests.
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Sends a request, retrying as necessary, and returns the final response.
+    ///
+    /// The response may still be an error, see [`parse_response`].
+    pub fn send_raw(&self, request: HttpRequest) -> Result<HttpResponse, ClientError<T::Error>> {
+        let mut attempt = 1;
+
+        loop {
+            let response = self
+                .transport
+                .send(request.clone())
+                .map_err(ClientError::Transport)?;
+
+            match self.retry_policy.retry_delay(attempt, &response) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Ok(response),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request and parses the response into the expected type.
+    pub fn send<R>(&self, request: HttpRequest) -> Result<R, ClientError<T::Error>>
+    where
+        R: TryFrom<ApiResponse, Error: Into<ConversionError>>,
+    {
+        let response = self.send_raw(request)?;
+        Ok(parse_response(&response)?)
+    }
+
+    /// Sends a messages request.
+    pub fn messages(
+        &self,
+        builder: &MessagesRequestBuilder,
+    ) -> Result<anthropic::MessagesResponse, ClientError<T::Error>> {
+        self.send(builder.build(&self.api))
+    }
+
+    /// Counts the tokens of a messages request, see
+    /// [`MessagesRequestBuilder::build_count_tokens`].
+    pub fn count_tokens(
+        &self,
+        builder: &MessagesRequestBuilder,
+    ) -> Result<anthropic::TokenCount, ClientError<T::Error>> {
+        self.send(builder.build_count_tokens(&self.api))
+    }
+
+    /// Sends a user message as part of a conversation, see [`Conversation::user_message`].
+    ///
+    /// If sending fails, the message is removed from the history again. Conversations with
+    /// streaming enabled are rejected.
+    pub fn user_message<S: Into<String>>(
+        &self,
+        conversation: &mut Conversation,
+        user_message: S,
+    ) -> Result<Action, ClientError<T::Error>> {
+        self.converse(conversation, |conversation, api| {
+            conversation.user_message(api, user_message)
+        })
+    }
+
+    /// Sends tool results as part of a conversation, see [`Conversation::tool_results`].
+    ///
+    /// If sending fails, the tool results are removed from the history again. Conversations with
+    /// streaming enabled are rejected.
+    pub fn tool_results(
+        &self,
+        conversation: &mut Conversation,
+        tool_results: Vec<anthropic::ToolResult>,
+    ) -> Result<Action, ClientError<T::Error>> {
+        self.converse(conversation, |conversation, api| {
+            conversation.tool_results(api, tool_results)
+        })
+    }
+
+    /// Sends the request built by `build`, restoring the conversation's history on failure.
+    fn converse<F>(
+        &self,
+        conversation: &mut Conversation,
+        build: F,
+    ) -> Result<Action, ClientError<T::Error>>
+    where
+        F: FnOnce(&mut Conversation, &Api) -> HttpRequest,
+    {
+        if conversation.is_streaming() {
+            return Err(ClientError::StreamingConversation);
+        }
+
+        let history = conversation.history().clone();
+        let request = build(conversation, &self.api);
+        match self.send(request) {
+            Ok(response) => Ok(conversation.commit_response(response)),
+            Err(err) => {
+                conversation.restore_history(history);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T> AsyncClient<T> {
+    /// Creates a new client with the default [`RetryPolicy`].
+    pub fn new(api: Api, transport: T) -> Self {
+        Self {
+            api,
+            transport,
+            retry_policy: RetryPolicy::new(),
+        }
+    }
+
+    /// Sets the retry policy.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Returns the [`Api`] used to build requests.
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: AsyncTransport> AsyncClient<T> {
+    /// Sends a request, retrying as necessary, and returns the final response.
+    ///
+    /// The response may still be an error, see [`parse_response`].
+    pub async fn send_raw(
+        &self,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, ClientError<T::Error>> {
+        let mut attempt = 1;
+
+        loop {
+            let response = self
+                .transport
+                .send(request.clone())
+                .await
+                .map_err(ClientError::Transport)?;
+
+            match self.retry_policy.retry_delay(attempt, &response) {
+                Some(delay) => self.transport.sleep(delay).await,
+                None => return Ok(response),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request and parses the response into the expected type.
+    pub async fn send<R>(&self, request: HttpRequest) -> Result<R, ClientError<T::Error>>
+    where
+        R: TryFrom<ApiResponse, Error: Into<ConversionError>>,
+    {
+        let response = self.send_raw(request).await?;
+        Ok(parse_response(&response)?)
+    }
+
+    /// Sends a messages request.
+    pub async fn messages(
+        &self,
+        builder: &MessagesRequestBuilder,
+    ) -> Result<anthropic::MessagesResponse, ClientError<T::Error>> {
+        self.send(builder.build(&self.api)).await
+    }
+
+    /// Counts the tokens of a messages request, see
+    /// [`MessagesRequestBuilder::build_count_tokens`].
+    pub async fn count_tokens(
+        &self,
+        builder: &MessagesRequestBuilder,
+    ) -> Result<anthropic::TokenCount, ClientError<T::Error>> {
+        self.send(builder.build_count_tokens(&self.api)).await
+    }
+
+    /// Sends a user message as part of a conversation, see [`Client::user_message`].
+    pub async fn user_message<S: Into<String>>(
+        &self,
+        conversation: &mut Conversation,
+        user_message: S,
+    ) -> Result<Action, ClientError<T::Error>> {
+        self.converse(conversation, |conversation, api| {
+            conversation.user_message(api, user_message)
+        })
+        .await
+    }
+
+    /// Sends tool results as part of a conversation, see [`Client::tool_results`].
+    pub async fn tool_results(
+        &self,
+        conversation: &mut Conversation,
+        tool_results: Vec<anthropic::ToolResult>,
+    ) -> Result<Action, ClientError<T::Error>> {
+        self.converse(conversation, |conversation, api| {
+            conversation.tool_results(api, tool_results)
+        })
+        .await
+    }
+
+    /// Sends the request built by `build`, restoring the conversation's history on failure.
+    async fn converse<F>(
+        &self,
+        conversation: &mut Conversation,
+        build: F,
+    ) -> Result<Action, ClientError<T::Error>>
+    where
+        F: FnOnce(&mut Conversation, &Api) -> HttpRequest,
+    {
+        if conversation.is_streaming() {
+            return Err(ClientError::StreamingConversation);
+        }
+
+        let history = conversation.history().clone();
+        let request = build(conversation, &self.api);
+        match self.send(request).await {
+            Ok(response) => Ok(conversation.commit_response(response)),
+            Err(err) => {
+                conversation.restore_history(history);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Error returned by the built-in `reqwest` transports.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, thiserror::Error)]
+pub enum ReqwestError {
+    /// The request could not be converted, e.g. due to an invalid header value.
+    #[error("Invalid request: {0}")]
+    InvalidRequest(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The request failed.
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+}
+
+#[cfg(feature = "reqwest")]
+impl AsyncTransport for reqwest::Client {
+    type Error = ReqwestError;
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        let response = request
+            .try_into_reqwest_builder(self)
+            .map_err(ReqwestError::InvalidRequest)?
+            .send()
+            .await?;
+
+        Ok(HttpResponse::try_from_reqwest(response).await?)
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[cfg(feature = "reqwest-blocking")]
+impl Transport for reqwest::blocking::Client {
+    type Error = ReqwestError;
+
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+        let request = request
+            .try_into_reqwest_blocking()
+            .map_err(ReqwestError::InvalidRequest)?;
+
+        Ok(HttpResponse::try_from_reqwest_blocking(
+            self.execute(request)?,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::VecDeque, sync::Mutex, time::Duration};
+
+    use super::{AsyncClient, AsyncTransport, Client, ClientError, Transport};
+    use crate::{
+        Api, MessagesRequestBuilder, ResponseError, anthropic::Content, conversation::Conversation,
+        http_request::HttpRequest, http_response::HttpResponse, retry::RetryPolicy,
+    };
+
+    const MESSAGE: &str = r#"{
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-sonnet-4-20250514",
+        "content": [{"type": "text", "text": "Hello!"}],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 10, "output_tokens": 5}
+    }"#;
+
+    /// Transport returning canned responses and recording the requests it was given.
+    #[derive(Default)]
+    struct MockTransport {
+        responses: RefCell<VecDeque<HttpResponse>>,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+
+    impl MockTransport {
+        fn new(responses: Vec<HttpResponse>) -> Self {
+            Self {
+                responses: RefCell::new(responses.into()),
+                requests: RefCell::default(),
+            }
+        }
+    }
+
+    impl Transport for MockTransport {
+        type Error = &'static str;
+
+        fn send(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+            self.requests.borrow_mut().push(request);
+            self.responses
+                .borrow_mut()
+                .pop_front()
+                .ok_or("no more responses")
+        }
+    }
+
+    /// Async counterpart of [`MockTransport`], recording requested sleeps instead of waiting.
+    #[derive(Default)]
+    struct AsyncMockTransport {
+        responses: Mutex<VecDeque<HttpResponse>>,
+        sleeps: Mutex<Vec<Duration>>,
+    }
+
+    impl AsyncTransport for AsyncMockTransport {
+        type Error = &'static str;
+
+        async fn send(&self, _request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or("no more responses")
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            self.sleeps.lock().unwrap().push(duration);
+        }
+    }
+
+    fn no_backoff() -> RetryPolicy {
+        RetryPolicy::new()
+            .initial_backoff(Duration::ZERO)
+            .jitter(0.0)
+    }
+
+    #[test]
+    fn test_client_retries_and_parses() {
+        let transport = MockTransport::new(vec![
+            HttpResponse::new(529, Vec::new(), ""),
+            HttpResponse::new(200, Vec::new(), MESSAGE),
+        ]);
+        let client = Client::new(Api::new("test-key"), transport).retry_policy(no_backoff());
+
+        let mut conversation = Conversation::new();
+        let action = client.user_message(&mut conversation, "Hi").unwrap();
+
+        assert!(matches!(&action.contents[..], [Content::Text { text, .. }] if text == "Hello!"));
+        assert_eq!(conversation.history().len(), 2);
+        assert_eq!(client.transport().requests.borrow().len(), 2);
+
+        // Non-retryable errors are returned as such.
+        let transport = MockTransport::new(vec![HttpResponse::new(
+            400,
+            Vec::new(),
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "Bad"}}"#,
+        )]);
+        let client = Client::new(Api::new("test-key"), transport).retry_policy(no_backoff());
+        let result = client.messages(&MessagesRequestBuilder::new());
+        assert!(matches!(
+            result,
+            Err(ClientError::Response(ResponseError::Api(_)))
+        ));
+        assert_eq!(client.transport().requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_client_conversation_failures() {
+        let transport = MockTransport::new(vec![HttpResponse::new(
+            400,
+            Vec::new(),
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "Bad"}}"#,
+        )]);
+        let client = Client::new(Api::new("test-key"), transport).retry_policy(no_backoff());
+
+        // Failed messages are removed from the history again.
+        let mut conversation = Conversation::new();
+        assert!(client.user_message(&mut conversation, "Hi").is_err());
+        assert!(conversation.history().is_empty());
+        assert!(matches!(
+            client.user_message(&mut conversation, "Hi"),
+            Err(ClientError::Transport("no more responses"))
+        ));
+        assert!(conversation.history().is_empty());
+
+        // Streaming conversations are rejected without sending anything.
+        conversation.set_stream(true);
+        assert!(matches!(
+            client.user_message(&mut conversation, "Hi"),
+            Err(ClientError::StreamingConversation)
+        ));
+        assert!(conversation.history().is_empty());
+        assert_eq!(client.transport().requests.borrow().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_async_client_gives_up() {
+        let transport = AsyncMockTransport {
+            responses: Mutex::new(
+                (0..3)
+                    .map(|_| HttpResponse::new(500, Vec::new(), ""))
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        let client = AsyncClient::new(Api::new("test-key"), transport)
+            .retry_policy(no_backoff().max_attempts(3));
+
+        let result = client.messages(&MessagesRequestBuilder::new()).await;
+        assert!(matches!(
+            result,
+            Err(ClientError::Response(ResponseError::HttpStatus {
+                status: 500,
+                ..
+            }))
+        ));
+        assert_eq!(client.transport().sleeps.lock().unwrap().len(), 2);
+        assert!(client.transport().responses.lock().unwrap().is_empty());
+    }
+}
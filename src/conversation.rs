@@ -19,6 +19,21 @@
 //! deserialized using [`serde`]. Additionally the convenience [`Conversation::to_json`] and
 //! [`Conversation::from_json`] methods can be used.
 //!
+//! ## Streaming
+//!
+//! If streaming is enabled through [`Conversation::set_stream`], requests will ask for a streamed
+//! response. Instead of calling [`Conversation::handle_response`], the caller obtains a
+//! [`ConversationStream`] from [`Conversation::begin_stream`] and feeds it the decoded events.
+//! The stream reports incremental [`StreamUpdate`]s suitable for display and, once the response
+//! is complete, commits it to the history and hands out the same [`Action`] as the non-streaming
+//! path.
+//!
+//! ## Prompt caching
+//!
+//! A [`CachingPolicy`] can be set through [`Conversation::set_caching_policy`] to have cache
+//! breakpoints placed automatically on every request. Breakpoints are only added to outgoing
+//! requests, the stored history remains unaltered.
+//!
 //! ## Example
 //!
 //! ```no_run
@@ -55,11 +70,16 @@
 //! ```
 //!
 
-use std::{io, sync::Arc};
+use std::io;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{Api, ResponseError, anthropic, anthropic::Message, http_request::HttpRequest};
+use crate::{
+    Api, ResponseError, anthropic,
+    anthropic::{Content, Delta, Message, StreamEvent},
+    http_request::HttpRequest,
+    streaming::{StreamAccumulator, StreamError},
+};
 
 /// Actions that the caller needs to take based on an API response.
 ///
@@ -76,15 +96,103 @@ pub struct Action {
     pub contents: Vec<anthropic::Content>,
 }
 
+/// Maximum number of cache breakpoints allowed in a single request.
+const MAX_CACHE_BREAKPOINTS: u8 = 4;
+
+/// Automatic prompt caching policy of a [`Conversation`].
+///
+/// When building a request, the conversation places [`anthropic::CacheControl`] breakpoints on
+/// the last tool, the system prompt and the most recent user turns. The rolling breakpoints on
+/// user turns move forward with the conversation: the breakpoint on the latest user turn writes
+/// the cache, while the one on the previous user turn ensures the entry written by the preceding
+/// request is read.
+///
+/// Breakpoints set manually on history contents count against the API limit of four breakpoints
+/// per request, so they should not be combined with automatic placement.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CachingPolicy {
+    /// Whether to place a breakpoint on the last tool definition.
+    pub tools: bool,
+    /// Whether to place a breakpoint on the system prompt.
+    pub system: bool,
+    /// Number of rolling breakpoints on the most recent user turns.
+    ///
+    /// Limited to however many breakpoints remain after tools and system prompt.
+    pub user_turns: u8,
+    /// Time-to-live of the cache entries, uses the API default if not set.
+    pub ttl: Option<anthropic::CacheTtl>,
+}
+
+impl CachingPolicy {
+    /// No automatic caching (the default).
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Caches tools, system prompt and the two most recent user turns.
+    pub fn automatic() -> Self {
+        Self {
+            tools: true,
+            system: true,
+            user_turns: 2,
+            ttl: None,
+        }
+    }
+
+    /// Sets the time-to-live of the cache entries.
+    pub fn ttl(mut self, ttl: anthropic::CacheTtl) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Returns the cache control to place on breakpoints.
+    fn cache_control(&self) -> anthropic::CacheControl {
+        anthropic::CacheControl::Ephemeral { ttl: self.ttl }
+    }
+}
+
 /// A conversation that manages message history and generates HTTP requests.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Conversation {
     /// The system prompt for the conversation.
-    system: Option<Arc<str>>,
+    system: Option<anthropic::SystemPrompt>,
     /// The conversation's message history.
     messages: im::Vector<anthropic::Message>,
     /// Tools available for the model to use.
     tools: im::Vector<anthropic::Tool>,
+    /// How the model should use the tools.
+    #[serde(default)]
+    tool_choice: Option<anthropic::ToolChoice>,
+    /// Extended thinking configuration.
+    #[serde(default)]
+    thinking: Option<anthropic::ThinkingConfig>,
+    /// Sampling temperature, overriding the [`Api`] default.
+    #[serde(default)]
+    temperature: Option<f32>,
+    /// Top K sampling cut-off, overriding the [`Api`] default.
+    #[serde(default)]
+    top_k: Option<u32>,
+    /// Nucleus sampling cut-off, overriding the [`Api`] default.
+    #[serde(default)]
+    top_p: Option<f32>,
+    /// Custom stop sequences, overriding the [`Api`] default.
+    #[serde(default)]
+    stop_sequences: Option<Vec<String>>,
+    /// Request metadata, overriding the [`Api`] default.
+    #[serde(default)]
+    metadata: Option<anthropic::Metadata>,
+    /// Service tier, overriding the [`Api`] default.
+    #[serde(default)]
+    service_tier: Option<anthropic::ServiceTier>,
+    /// Automatic prompt caching policy.
+    #[serde(default)]
+    caching: CachingPolicy,
+    /// Whether to request streamed responses.
+    #[serde(default)]
+    stream: bool,
+    /// Token usage of the most recent response.
+    #[serde(skip)]
+    last_usage: Option<anthropic::Usage>,
 }
 
 impl Conversation {
@@ -94,17 +202,101 @@ impl Conversation {
             system: None,
             messages: im::Vector::new(),
             tools: im::Vector::new(),
+            tool_choice: None,
+            thinking: None,
+            temperature: None,
+            top_k: None,
+            top_p: None,
+            stop_sequences: None,
+            metadata: None,
+            service_tier: None,
+            caching: CachingPolicy::default(),
+            stream: false,
+            last_usage: None,
         }
     }
 
     /// Sets the system prompt for the conversation.
     ///
     /// By default, the system prompt is not set.
-    pub fn set_system<S: Into<Arc<str>>>(&mut self, system: S) -> &mut Self {
+    pub fn set_system<S: Into<anthropic::SystemPrompt>>(&mut self, system: S) -> &mut Self {
         self.system = Some(system.into());
         self
     }
 
+    /// Sets the extended thinking configuration for the conversation.
+    ///
+    /// Thinking blocks returned by the model are kept in the history verbatim, as required by the
+    /// API when using tools with extended thinking. By default, thinking is not configured.
+    pub fn set_thinking(&mut self, thinking: anthropic::ThinkingConfig) -> &mut Self {
+        self.thinking = Some(thinking);
+        self
+    }
+
+    /// Sets the sampling temperature for the conversation.
+    ///
+    /// By default, the temperature set on the [`Api`] is used.
+    pub fn set_temperature(&mut self, temperature: f32) -> &mut Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets the top K sampling cut-off for the conversation.
+    pub fn set_top_k(&mut self, top_k: u32) -> &mut Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Sets the nucleus sampling cut-off for the conversation.
+    pub fn set_top_p(&mut self, top_p: f32) -> &mut Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Sets the custom stop sequences for the conversation.
+    pub fn set_stop_sequences<I, S>(&mut self, stop_sequences: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stop_sequences = Some(stop_sequences.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the request metadata for the conversation.
+    pub fn set_metadata(&mut self, metadata: anthropic::Metadata) -> &mut Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets the service tier for the conversation.
+    pub fn set_service_tier(&mut self, service_tier: anthropic::ServiceTier) -> &mut Self {
+        self.service_tier = Some(service_tier);
+        self
+    }
+
+    /// Sets the automatic prompt caching policy for the conversation.
+    ///
+    /// By default, no cache breakpoints are placed, see [`CachingPolicy`].
+    pub fn set_caching_policy(&mut self, caching: CachingPolicy) -> &mut Self {
+        self.caching = caching;
+        self
+    }
+
+    /// Sets whether requests should ask for streamed responses.
+    ///
+    /// Responses to streamed requests must be handled through [`Conversation::begin_stream`]
+    /// instead of [`Conversation::handle_response`]. By default, streaming is disabled.
+    pub fn set_stream(&mut self, stream: bool) -> &mut Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Returns whether requests ask for streamed responses, see [`Conversation::set_stream`].
+    pub fn is_streaming(&self) -> bool {
+        self.stream
+    }
+
     /// Adds a user message and returns an HTTP request to send.
     ///
     /// The message will automatically be added to the conversation history.
@@ -113,6 +305,18 @@ impl Conversation {
         self.build_message(api, message)
     }
 
+    /// Adds a user message with arbitrary contents and returns an HTTP request to send.
+    ///
+    /// Use this to send multipart messages, e.g. text alongside images. The message will
+    /// automatically be added to the conversation history.
+    pub fn user_contents(&mut self, api: &Api, content: Vec<anthropic::Content>) -> HttpRequest {
+        let message = anthropic::Message {
+            role: anthropic::Role::User,
+            content,
+        };
+        self.build_message(api, message)
+    }
+
     /// Adds tool results to the conversation and returns an HTTP request to send.
     ///
     /// The tool results will be added as a user message to the conversation history.
@@ -137,17 +341,88 @@ impl Conversation {
     fn build_message(&mut self, api: &Api, message: anthropic::Message) -> HttpRequest {
         self.messages.push_back(message);
 
-        let mut builder = crate::MessagesRequestBuilder::new().set_messages(self.messages.clone());
+        let caching = self.caching;
+        let mut breakpoints = MAX_CACHE_BREAKPOINTS;
+
+        let mut tools = self.tools.clone();
+        if caching.tools
+            && let Some(last) = tools.back_mut()
+        {
+            last.cache_control = Some(caching.cache_control());
+            breakpoints -= 1;
+        }
+
+        let mut system = self.system.clone();
+        if caching.system
+            && let Some(prompt) = system.take()
+        {
+            system = Some(prompt.with_cache_control(caching.cache_control()));
+            breakpoints -= 1;
+        }
 
-        if let Some(ref system) = self.system {
-            builder = builder.system(system.clone());
+        let mut messages = self.messages.clone();
+        let mut user_turns = caching.user_turns.min(breakpoints);
+        for message in messages.iter_mut().rev() {
+            if user_turns == 0 {
+                break;
+            }
+            if message.role != anthropic::Role::User {
+                continue;
+            }
+            // Place the breakpoint on the last block that supports it.
+            if message
+                .content
+                .iter_mut()
+                .rev()
+                .any(|content| content.set_cache_control(Some(caching.cache_control())))
+            {
+                user_turns -= 1;
+            }
         }
 
-        if !self.tools.is_empty() {
-            builder = builder.set_tools(self.tools.clone());
+        let mut builder = crate::MessagesRequestBuilder::new().set_messages(messages);
+
+        if let Some(system) = system {
+            builder = builder.system(system);
         }
 
-        builder.build(api)
+        if !tools.is_empty() {
+            builder = builder.set_tools(tools);
+        }
+
+        if let Some(ref tool_choice) = self.tool_choice {
+            builder = builder.tool_choice(tool_choice.clone());
+        }
+
+        if let Some(thinking) = self.thinking {
+            builder = builder.thinking(thinking);
+        }
+
+        if let Some(temperature) = self.temperature {
+            builder = builder.temperature(temperature);
+        }
+
+        if let Some(top_k) = self.top_k {
+            builder = builder.top_k(top_k);
+        }
+
+        if let Some(top_p) = self.top_p {
+            builder = builder.top_p(top_p);
+        }
+
+        if let Some(ref stop_sequences) = self.stop_sequences {
+            builder = builder.stop_sequences(stop_sequences.iter().cloned());
+        }
+
+        if let Some(ref metadata) = self.metadata {
+            builder = builder.metadata(metadata.clone());
+        }
+
+        if let Some(service_tier) = self.service_tier {
+            builder = builder.service_tier(service_tier);
+        }
+
+        builder.stream(self.stream).build(api)
     }
 
     /// Handles the response from the API and returns the actions to take.
@@ -160,12 +435,29 @@ impl Conversation {
     pub fn handle_response(&mut self, response_json: &str) -> Result<Action, ResponseError> {
         let response: anthropic::MessagesResponse = crate::deserialize_response(response_json)?;
 
+        Ok(self.commit_response(response))
+    }
+
+    /// Begins handling a streamed response.
+    ///
+    /// The returned [`ConversationStream`] must be fed all events of the response, see its
+    /// documentation for details.
+    pub fn begin_stream(&mut self) -> ConversationStream<'_> {
+        ConversationStream {
+            conversation: self,
+            accumulator: StreamAccumulator::new(),
+        }
+    }
+
+    /// Adds a complete response to the history and returns the resulting action.
+    pub(crate) fn commit_response(&mut self, response: anthropic::MessagesResponse) -> Action {
         // Add assistant's message to history
         self.messages.push_back(response.message.clone());
+        self.last_usage = Some(response.usage);
 
-        Ok(Action {
+        Action {
             contents: response.message.content,
-        })
+        }
     }
 
     /// Serializes the conversation to JSON using the provided writer.
@@ -183,11 +475,30 @@ impl Conversation {
         self.messages = im::Vector::new();
     }
 
+    /// Returns the token usage reported with the most recent response, if any.
+    pub fn last_usage(&self) -> Option<&anthropic::Usage> {
+        self.last_usage.as_ref()
+    }
+
+    /// Returns the cache hit ratio of the most recent response.
+    ///
+    /// See [`anthropic::Usage::cache_hit_ratio`] for details.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        self.last_usage
+            .as_ref()
+            .map(anthropic::Usage::cache_hit_ratio)
+    }
+
     /// Returns the message history.
     pub fn history(&self) -> &im::Vector<Message> {
         &self.messages
     }
 
+    /// Replaces the message history with an earlier copy, e.g. after a request failed.
+    pub(crate) fn restore_history(&mut self, messages: im::Vector<Message>) {
+        self.messages = messages;
+    }
+
     /// Adds a tool to the conversation.
     ///
     /// Tools are available to the model and will be included in all subsequent requests.
@@ -203,6 +514,21 @@ impl Conversation {
         self.tools = tools.into();
         self
     }
+
+    /// Sets how the model should use the conversation's tools.
+    ///
+    /// Applies to all subsequent requests until changed again or cleared with
+    /// [`Conversation::clear_tool_choice`].
+    pub fn set_tool_choice(&mut self, tool_choice: anthropic::ToolChoice) -> &mut Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    /// Clears the tool choice, letting the model decide whether to use tools.
+    pub fn clear_tool_choice(&mut self) -> &mut Self {
+        self.tool_choice = None;
+        self
+    }
 }
 
 impl Default for Conversation {
@@ -211,11 +537,143 @@ impl Default for Conversation {
     }
 }
 
+/// An incremental update while streaming a response, see [`ConversationStream::push`].
+#[derive(Debug)]
+pub enum StreamUpdate {
+    /// Nothing to display, e.g. after a ping.
+    None,
+    /// A content block has started.
+    ///
+    /// Text blocks may already contain text.
+    BlockStart {
+        /// Index of the content block.
+        index: u32,
+        /// The content block as started.
+        content: Content,
+    },
+    /// Text has been appended to a text block.
+    Text {
+        /// Index of the content block.
+        index: u32,
+        /// The appended text.
+        text: String,
+    },
+    /// Reasoning has been appended to a thinking block.
+    Thinking {
+        /// Index of the content block.
+        index: u32,
+        /// The appended reasoning.
+        thinking: String,
+    },
+    /// A fragment of tool input JSON has been received.
+    ToolInput {
+        /// Index of the content block.
+        index: u32,
+        /// The received fragment, see [`StreamAccumulator::partial_input`] for parsing the input
+        /// received so far.
+        partial_json: String,
+    },
+    /// A content block is complete.
+    BlockStop {
+        /// Index of the content block.
+        index: u32,
+        /// The complete content block.
+        content: Content,
+    },
+    /// The response is complete and has been added to the conversation history.
+    ///
+    /// The action must be handled just like one returned by [`Conversation::handle_response`].
+    Done(Action),
+}
+
+/// A streamed response being received by a [`Conversation`].
+///
+/// Created through [`Conversation::begin_stream`]. All events of the response must be passed to
+/// [`ConversationStream::push`] in order. Once the final `message_stop` event has been pushed,
+/// [`StreamUpdate::Done`] is returned and the assembled assistant message is part of the history.
+///
+/// If the stream is aborted or fails, nothing is added to the history.
+#[derive(Debug)]
+pub struct ConversationStream<'a> {
+    /// The conversation the response belongs to.
+    conversation: &'a mut Conversation,
+    /// Accumulates the response.
+    accumulator: StreamAccumulator,
+}
+
+impl ConversationStream<'_> {
+    /// Processes a single streaming event.
+    pub fn push(&mut self, event: StreamEvent) -> Result<StreamUpdate, StreamError> {
+        let update = match event {
+            StreamEvent::ContentBlockStart {
+                index,
+                ref content_block,
+            } => StreamUpdate::BlockStart {
+                index,
+                content: content_block.clone(),
+            },
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: Delta::TextDelta { ref text },
+            } => StreamUpdate::Text {
+                index,
+                text: text.clone(),
+            },
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: Delta::ThinkingDelta { ref thinking },
+            } => StreamUpdate::Thinking {
+                index,
+                thinking: thinking.clone(),
+            },
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: Delta::InputJsonDelta { ref partial_json },
+            } => StreamUpdate::ToolInput {
+                index,
+                partial_json: partial_json.clone(),
+            },
+            _ => StreamUpdate::None,
+        };
+
+        let stop_index = match event {
+            StreamEvent::ContentBlockStop { index } => Some(index),
+            _ => None,
+        };
+
+        self.accumulator.push(event)?;
+
+        if let Some(index) = stop_index {
+            let content = self
+                .accumulator
+                .content(index)
+                .cloned()
+                .ok_or(StreamError::UnknownBlock(index))?;
+            return Ok(StreamUpdate::BlockStop { index, content });
+        }
+
+        if self.accumulator.is_complete() {
+            let accumulator = std::mem::take(&mut self.accumulator);
+            let response = accumulator.finish()?;
+            return Ok(StreamUpdate::Done(
+                self.conversation.commit_response(response),
+            ));
+        }
+
+        Ok(update)
+    }
+
+    /// Returns the accumulator of the response.
+    pub fn accumulator(&self) -> &StreamAccumulator {
+        &self.accumulator
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use schemars::JsonSchema;
 
-    use crate::conversation::Conversation;
+    use crate::conversation::{CachingPolicy, Conversation, StreamUpdate};
 
     #[derive(JsonSchema)]
     #[allow(dead_code)]
@@ -242,16 +700,239 @@ mod tests {
         let http_request = conversation.user_message(&api, "Hello, use the tool!");
 
         // Verify the request includes tools
-        assert!(http_request.body.contains("\"tools\":["));
-        assert!(http_request.body.contains("\"name\":\"test_tool\""));
+        assert!(http_request.body.as_json().unwrap().contains("\"tools\":["));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"name\":\"test_tool\"")
+        );
         assert!(
             http_request
                 .body
+                .as_json()
+                .unwrap()
                 .contains("\"description\":\"A test tool for testing\"")
         );
 
         // Verify the message is also present
-        assert!(http_request.body.contains("\"messages\":["));
-        assert!(http_request.body.contains("\"Hello, use the tool!\""));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"messages\":[")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"Hello, use the tool!\"")
+        );
+    }
+
+    #[test]
+    fn test_conversation_with_image() {
+        let api = crate::Api::new("test-api-key");
+        let mut conversation = Conversation::new();
+
+        let http_request = conversation.user_contents(
+            &api,
+            vec![
+                crate::anthropic::Content::image_from_url("https://example.com/cat.jpg"),
+                crate::anthropic::Content::from_text("What is in this image?"),
+            ],
+        );
+
+        assert!(http_request.body.as_json().unwrap().contains(
+            r#"{"type":"image","source":{"type":"url","url":"https://example.com/cat.jpg"}}"#
+        ));
+        assert_eq!(conversation.history().len(), 1);
+        assert_eq!(conversation.history()[0].content.len(), 2);
+    }
+
+    #[test]
+    fn test_conversation_keeps_thinking_blocks() {
+        let api = crate::Api::new("test-api-key");
+        let mut conversation = Conversation::new();
+        conversation.set_thinking(crate::anthropic::ThinkingConfig::enabled(1024));
+
+        let http_request = conversation.user_message(&api, "What's the weather?");
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains(r#""thinking":{"type":"enabled","budget_tokens":1024}"#)
+        );
+
+        let response_json = r#"{"type":"message","id":"msg_123","model":"claude-sonnet-4-20250514","stop_reason":"tool_use","stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":5},"role":"assistant","content":[{"type":"thinking","thinking":"I should use the tool.","signature":"sig_123"},{"type":"redacted_thinking","data":"abc"},{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}]}"#;
+        conversation
+            .handle_response(response_json)
+            .expect("should handle response");
+
+        let http_request = conversation.tool_results(
+            &api,
+            vec![crate::anthropic::ToolResult::success(
+                "toolu_1".to_string(),
+                "sunny",
+            )],
+        );
+
+        // Thinking blocks must be echoed back verbatim.
+        assert!(http_request.body.as_json().unwrap().contains(
+            r#"{"type":"thinking","thinking":"I should use the tool.","signature":"sig_123"}"#
+        ));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains(r#"{"type":"redacted_thinking","data":"abc"}"#)
+        );
+    }
+
+    #[test]
+    fn test_conversation_overrides_api_defaults() {
+        let api = crate::Api::new("test-api-key")
+            .default_temperature(1.0)
+            .default_top_k(10);
+        let mut conversation = Conversation::new();
+        conversation
+            .set_temperature(0.0)
+            .set_stop_sequences(["</answer>"]);
+
+        let http_request = conversation.user_message(&api, "Hello!");
+        let body: serde_json::Value =
+            serde_json::from_str(http_request.body.as_json().unwrap()).unwrap();
+        assert_eq!(body["temperature"], 0.0);
+        assert_eq!(body["top_k"], 10);
+        assert_eq!(body["stop_sequences"], serde_json::json!(["</answer>"]));
+    }
+
+    #[test]
+    fn test_conversation_with_forced_tool() {
+        let api = crate::Api::new("test-api-key");
+        let mut conversation = Conversation::new();
+        conversation
+            .add_tool(crate::anthropic::Tool::new::<TestToolInput, _, _>(
+                "test_tool",
+                "A test tool for testing",
+            ))
+            .set_tool_choice(crate::anthropic::ToolChoice::tool("test_tool"));
+
+        let http_request = conversation.user_message(&api, "Hello!");
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains(r#""tool_choice":{"type":"tool","name":"test_tool"}"#)
+        );
+
+        conversation.clear_tool_choice();
+        let http_request = conversation.user_message(&api, "Hello again!");
+        assert!(!http_request.body.as_json().unwrap().contains("tool_choice"));
+    }
+
+    #[test]
+    fn test_conversation_automatic_caching() {
+        let api = crate::Api::new("test-api-key");
+        let mut conversation = Conversation::new();
+        conversation
+            .set_system("You are a helpful assistant.")
+            .add_tool(crate::anthropic::Tool::new::<TestToolInput, _, _>(
+                "test_tool",
+                "A test tool for testing",
+            ))
+            .set_caching_policy(CachingPolicy::automatic());
+
+        let response_json = r#"{"type":"message","id":"msg_123","model":"claude-sonnet-4-20250514","stop_reason":"end_turn","stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":5,"cache_creation_input_tokens":0,"cache_read_input_tokens":30},"role":"assistant","content":[{"type":"text","text":"Hi!"}]}"#;
+
+        conversation.user_message(&api, "First");
+        conversation
+            .handle_response(response_json)
+            .expect("should handle response");
+        conversation.user_message(&api, "Second");
+        conversation
+            .handle_response(response_json)
+            .expect("should handle response");
+        let http_request = conversation.user_message(&api, "Third");
+
+        let body: serde_json::Value =
+            serde_json::from_str(http_request.body.as_json().unwrap()).unwrap();
+        let ephemeral = serde_json::json!({"type": "ephemeral"});
+
+        assert_eq!(body["tools"][0]["cache_control"], ephemeral);
+        assert_eq!(body["system"][0]["cache_control"], ephemeral);
+
+        let messages = body["messages"].as_array().unwrap();
+        assert!(messages[0]["content"][0].get("cache_control").is_none());
+        assert_eq!(messages[2]["content"][0]["cache_control"], ephemeral);
+        assert_eq!(messages[4]["content"][0]["cache_control"], ephemeral);
+
+        // The stored history must remain free of breakpoints.
+        assert!(
+            conversation
+                .history()
+                .iter()
+                .flat_map(|message| message.content.iter())
+                .all(|content| content.cache_control().is_none())
+        );
+
+        assert_eq!(conversation.cache_hit_ratio(), Some(0.75));
+    }
+
+    #[test]
+    fn test_conversation_streaming() {
+        let api = crate::Api::new("test-api-key");
+        let mut conversation = Conversation::new();
+        conversation.set_stream(true);
+
+        let http_request = conversation.user_message(&api, "Hello!");
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains(r#""stream":true"#)
+        );
+
+        let events = [
+            r#"{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi "}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"there!"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":5}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+
+        let mut text = String::new();
+        let mut action = None;
+        let mut stream = conversation.begin_stream();
+        for event in events {
+            let event = crate::deserialize_event(event.as_bytes()).unwrap();
+            match stream.push(event).expect("should push event") {
+                StreamUpdate::Text { text: fragment, .. } => text.push_str(&fragment),
+                StreamUpdate::BlockStop { content, .. } => {
+                    assert_eq!(content.as_text(), Some("Hi there!"));
+                }
+                StreamUpdate::Done(done) => action = Some(done),
+                _ => {}
+            }
+        }
+
+        assert_eq!(text, "Hi there!");
+        let action = action.expect("should be done");
+        assert_eq!(action.contents.len(), 1);
+        assert_eq!(conversation.history().len(), 2);
+        assert_eq!(
+            conversation.history()[1].content[0].as_text(),
+            Some("Hi there!")
+        );
+        assert_eq!(conversation.last_usage().unwrap().output_tokens, 5);
     }
 }
@@ -0,0 +1,225 @@
+//! Files API (beta).
+//!
+//! Files can be uploaded once and then referenced from any number of requests by their ID, e.g.
+//! using [`Document::from_file`](crate::anthropic::Document::from_file) or
+//! [`Content::image_from_file`](crate::anthropic::Content::image_from_file). See
+//! <https://docs.anthropic.com/en/docs/build-with-claude/files> for details.
+//!
+//! All requests built by this module enable [`Beta::FilesApi`]. Messages requests referencing
+//! uploaded files must enable it as well, see [`crate::MessagesRequestBuilder::beta`].
+//!
+//! ## Example
+//!
+//! ```
+//! use claus::{Api, anthropic::FileMetadata, files};
+//!
+//! let api = Api::new("sk-ant-api03-...");
+//!
+//! let http_req = files::upload(&api, "report.pdf", "application/pdf", b"%PDF-1.7 ...".to_vec());
+//! assert_eq!(http_req.path, "/v1/files");
+//!
+//! // Once sent, the response contains the ID of the uploaded file.
+//! let json = r#"{
+//!     "type": "file",
+//!     "id": "file_011CNha8iCJcU1wXNR6q4V8w",
+//!     "filename": "report.pdf",
+//!     "mime_type": "application/pdf",
+//!     "size_bytes": 1024,
+//!     "created_at": "2025-01-01T00:00:00Z",
+//!     "downloadable": false
+//! }"#;
+//! let file: FileMetadata = claus::deserialize_response(json).unwrap();
+//! assert_eq!(file.id, "file_011CNha8iCJcU1wXNR6q4V8w");
+//! ```
+
+use crate::{
+    Api, ListRequestBuilder, Listable,
+    anthropic::{Beta, FileMetadata},
+    apply_request_headers,
+    http_request::{Body, HttpRequest, Multipart, path_segment},
+};
+
+/// Path of the files endpoint.
+const FILES_PATH: &str = "/v1/files";
+
+/// Builder for a request listing uploaded files.
+///
+/// Once sent, you should expect to receive a [`Page<FileMetadata>`](crate::anthropic::Page) from
+/// the API.
+pub type ListFilesRequestBuilder = ListRequestBuilder<FileMetadata>;
+
+impl Listable for FileMetadata {
+    fn list_request(api: &Api) -> HttpRequest {
+        create_files_request(api, "GET", FILES_PATH, Body::Empty)
+    }
+}
+
+/// Builds an HTTP request uploading a file.
+///
+/// The request body is `multipart/form-data` encoded. Once sent, you should expect to receive a
+/// [`FileMetadata`](crate::anthropic::FileMetadata) from the API.
+pub fn upload<F, M, D>(api: &Api, filename: F, mime_type: M, data: D) -> HttpRequest
+where
+    F: Into<String>,
+    M: Into<String>,
+    D: Into<Vec<u8>>,
+{
+    let multipart = Multipart::new().file("file", filename, mime_type, data);
+    create_files_request(api, "POST", FILES_PATH, multipart)
+}
+
+/// Builds an HTTP request retrieving the metadata of a file.
+///
+/// Once sent, you should expect to receive a [`FileMetadata`](crate::anthropic::FileMetadata)
+/// from the API.
+///
+/// # Panics
+///
+/// Panics if `file_id` is empty, `.` or `..`.
+pub fn retrieve(api: &Api, file_id: &str) -> HttpRequest {
+    create_files_request(
+        api,
+        "GET",
+        format!("{FILES_PATH}/{}", path_segment(file_id)),
+        Body::Empty,
+    )
+}
+
+/// Builds an HTTP request downloading the contents of a file.
+///
+/// Only files marked as [`downloadable`](crate::anthropic::FileMetadata::downloadable) can be
+/// downloaded. The response body is the raw file contents, not JSON.
+///
+/// # Panics
+///
+/// Panics if `file_id` is empty, `.` or `..`.
+pub fn download(api: &Api, file_id: &str) -> HttpRequest {
+    create_files_request(
+        api,
+        "GET",
+        format!("{FILES_PATH}/{}/content", path_segment(file_id)),
+        Body::Empty,
+    )
+}
+
+/// Builds an HTTP request deleting a file.
+///
+/// Once sent, you should expect to receive a [`Deleted`](crate::anthropic::Deleted) from the
+/// API.
+///
+/// # Panics
+///
+/// Panics if `file_id` is empty, `.` or `..`.
+pub fn delete(api: &Api, file_id: &str) -> HttpRequest {
+    create_files_request(
+        api,
+        "DELETE",
+        format!("{FILES_PATH}/{}", path_segment(file_id)),
+        Body::Empty,
+    )
+}
+
+/// Creates a request with the Files API beta enabled.
+fn create_files_request<P: AsRef<str>, B: Into<Body>>(
+    api: &Api,
+    method: &'static str,
+    path: P,
+    body: B,
+) -> HttpRequest {
+    let mut request = api.create_request(method, path, body);
+    apply_request_headers(&mut request.headers, &[Beta::FilesApi], &[]);
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListFilesRequestBuilder;
+    use crate::{
+        Api,
+        anthropic::{Content, Deleted, Document, FileMetadata},
+        deserialize_response,
+        http_request::Body,
+    };
+
+    #[test]
+    fn test_upload_request() {
+        let api = Api::new("test-key");
+
+        let http_req = super::upload(&api, "notes.txt", "text/plain", b"some notes".to_vec());
+        assert_eq!(http_req.method, "POST");
+        assert_eq!(http_req.path, "/v1/files");
+
+        let Body::Multipart(ref multipart) = http_req.body else {
+            panic!("expected multipart body, got {:?}", http_req.body);
+        };
+        let boundary = multipart.boundary();
+
+        let headers = http_req.render_headers();
+        assert!(headers.contains(&format!(
+            "content-type: multipart/form-data; boundary={boundary}"
+        )));
+        assert!(headers.contains("anthropic-beta: files-api-2025-04-14"));
+
+        let expected = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\n\
+             Content-Type: text/plain\r\n\
+             \r\n\
+             some notes\r\n\
+             --{boundary}--\r\n"
+        );
+        assert_eq!(http_req.body.to_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_file_management_requests() {
+        let api = Api::new("test-key");
+
+        let http_req = ListFilesRequestBuilder::new().limit(5).build(&api);
+        assert_eq!(http_req.path_and_query(), "/v1/files?limit=5");
+        assert!(
+            http_req
+                .render_headers()
+                .contains("anthropic-beta: files-api-2025-04-14")
+        );
+
+        assert_eq!(super::retrieve(&api, "file_01").path, "/v1/files/file_01");
+        assert_eq!(
+            super::download(&api, "file_01").path,
+            "/v1/files/file_01/content"
+        );
+        assert_eq!(super::delete(&api, "file_01").method, "DELETE");
+        assert_eq!(
+            super::download(&api, "file_01?x=1").path,
+            "/v1/files/file_01%3Fx%3D1/content"
+        );
+
+        let deleted: Deleted =
+            deserialize_response(r#"{"id": "file_01", "type": "file_deleted"}"#).unwrap();
+        assert_eq!(deleted.id, "file_01");
+
+        let file: FileMetadata = deserialize_response(
+            r#"{"type": "file", "id": "file_01", "filename": "a.png", "mime_type": "image/png", "size_bytes": 3, "created_at": "2025-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(!file.downloadable);
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_dot_segment_file_id() {
+        super::download(&Api::new("test-key"), ".");
+    }
+
+    #[test]
+    fn test_file_content_sources() {
+        assert_eq!(
+            serde_json::to_value(Content::image_from_file("file_01")).unwrap(),
+            serde_json::json!({"type": "image", "source": {"type": "file", "file_id": "file_01"}})
+        );
+        assert_eq!(
+            serde_json::to_value(Content::document(Document::from_file("file_02"))).unwrap(),
+            serde_json::json!({"type": "document", "source": {"type": "file", "file_id": "file_02"}})
+        );
+    }
+}
@@ -8,8 +8,17 @@
 //! If the `reqwest`/`reqwest-blocking` feature is enabled, the [`HttpRequest`] type can be
 //! converted to a [`reqwest::Request`] or [`reqwest::blocking::Request`] using the
 //! `try_into_reqwest` or `try_into_reqwest_blocking` methods.
+//!
+//! If the `http` feature is enabled, the [`HttpRequest`] type can be converted to an
+//! `http::Request<Vec<u8>>`, which can be sent by most HTTP clients in the ecosystem, e.g. `hyper`
+//! or `ureq`.
 
-use std::{fmt, sync::Arc};
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
 
 /// HTTP request encapsulation.
 ///
@@ -25,72 +34,632 @@ use std::{fmt, sync::Arc};
 ///
 /// Additionally, the `From<HttpRequest>` trait is implemented for `reqwest::Request` and
 /// `reqwest::blocking::Request`, beware that it will panic if the conversion fails.
-#[derive(Debug)]
+///
+/// ## Redaction
+///
+/// The values of headers listed in [`HttpRequest::secret_headers`] are redacted when the request
+/// is printed through [`std::fmt::Display`], [`std::fmt::Debug`] or
+/// [`HttpRequest::render_headers`], so requests can be logged without leaking the API key. Use
+/// [`HttpRequest::render_unredacted`] if the actual values are needed.
+#[derive(Clone)]
 pub struct HttpRequest {
-    /// Request host.
+    /// URL scheme, usually `https`.
+    pub scheme: String,
+    /// Request host, including the port if not the default one.
     pub host: String,
-    /// Request path.
+    /// Request path, without query string.
     pub path: String,
+    /// Query parameters, unencoded.
+    pub query: Vec<(String, String)>,
     /// HTTP method.
-    pub method: &'static str,
+    pub method: Cow<'static, str>,
     /// Request headers.
-    pub headers: Vec<(&'static str, Arc<str>)>,
+    ///
+    /// Does not include the `content-type` header, which is derived from the body unless set
+    /// explicitly.
+    pub headers: Vec<(Cow<'static, str>, Arc<str>)>,
+    /// Names of headers whose values are redacted when printing the request.
+    ///
+    /// Compared case-insensitively, defaults to [`DEFAULT_SECRET_HEADERS`].
+    pub secret_headers: Arc<[Cow<'static, str>]>,
     /// Request body.
-    pub body: String,
+    pub body: Body,
+}
+
+/// Headers redacted by default, as they carry credentials.
+pub const DEFAULT_SECRET_HEADERS: &[Cow<'static, str>] = &[
+    Cow::Borrowed("x-api-key"),
+    Cow::Borrowed("authorization"),
+    Cow::Borrowed("proxy-authorization"),
+];
+
+/// Placeholder for redacted header values.
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// Body of an HTTP request.
+///
+/// Binary data is replaced with its length when debug printing.
+#[derive(Clone, Default)]
+pub enum Body {
+    /// No body, e.g. for `GET` requests.
+    #[default]
+    Empty,
+    /// JSON text, sent with a content type of `application/json`.
+    Json(String),
+    /// Raw bytes with the given content type.
+    Bytes {
+        /// Content type of the data.
+        content_type: String,
+        /// The data.
+        data: Vec<u8>,
+    },
+    /// A `multipart/form-data` body, used for file uploads.
+    Multipart(Multipart),
+}
+
+impl Body {
+    /// Returns the JSON text of the body, if it is a JSON body.
+    pub fn as_json(&self) -> Option<&str> {
+        match self {
+            Body::Json(json) => Some(json),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the body is empty.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Body::Empty)
+    }
+
+    /// Returns the content type of the body, `None` if the body is empty.
+    pub fn content_type(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Body::Empty => None,
+            Body::Json(_) => Some(Cow::Borrowed("application/json")),
+            Body::Bytes { content_type, .. } => Some(Cow::Borrowed(content_type)),
+            Body::Multipart(multipart) => Some(Cow::Owned(multipart.content_type())),
+        }
+    }
+
+    /// Encodes the body into the raw bytes to be sent.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Body::Empty => Vec::new(),
+            Body::Json(json) => json.as_bytes().to_vec(),
+            Body::Bytes { data, .. } => data.clone(),
+            Body::Multipart(multipart) => multipart.encode(),
+        }
+    }
+
+    /// Converts the body into the raw bytes to be sent, avoiding a copy where possible.
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Body::Json(json) => json.into_bytes(),
+            Body::Bytes { data, .. } => data,
+            other => other.to_bytes(),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    /// Creates a body of raw bytes with a content type of `application/octet-stream`.
+    fn from(data: Vec<u8>) -> Self {
+        Body::Bytes {
+            content_type: "application/octet-stream".to_string(),
+            data,
+        }
+    }
+}
+
+impl From<String> for Body {
+    fn from(json: String) -> Self {
+        Body::Json(json)
+    }
+}
+
+impl From<Multipart> for Body {
+    fn from(multipart: Multipart) -> Self {
+        Body::Multipart(multipart)
+    }
+}
+
+/// A `multipart/form-data` body.
+///
+/// The body is encoded by the crate itself, so it can be sent using any HTTP client. The boundary
+/// is derived from the contents and guaranteed not to occur in any part.
+#[derive(Clone, Debug, Default)]
+pub struct Multipart {
+    /// The parts of the body, in order.
+    parts: Vec<Part>,
+}
+
+/// A single part of a [`Multipart`] body.
+///
+/// The data is replaced with its length when debug printing.
+#[derive(Clone)]
+pub struct Part {
+    /// Name of the form field.
+    pub name: String,
+    /// File name, if the part is a file.
+    pub filename: Option<String>,
+    /// Content type of the part, if any.
+    pub content_type: Option<String>,
+    /// Contents of the part.
+    pub data: Vec<u8>,
+}
+
+impl Multipart {
+    /// Creates a new, empty multipart body.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a text field.
+    pub fn text<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            data: value.into().into_bytes(),
+        });
+        self
+    }
+
+    /// Adds a file field.
+    pub fn file<N, F, C, D>(mut self, name: N, filename: F, content_type: C, data: D) -> Self
+    where
+        N: Into<String>,
+        F: Into<String>,
+        C: Into<String>,
+        D: Into<Vec<u8>>,
+    {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: Some(filename.into()),
+            content_type: Some(content_type.into()),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// Returns the parts of the body.
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Returns the boundary separating the parts.
+    pub fn boundary(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        for part in &self.parts {
+            part.name.hash(&mut hasher);
+            part.data.hash(&mut hasher);
+        }
+        let mut seed = hasher.finish();
+
+        loop {
+            let boundary = format!("claus-boundary-{seed:016x}");
+            if !self
+                .parts
+                .iter()
+                .any(|part| contains(&part.data, boundary.as_bytes()))
+            {
+                return boundary;
+            }
+            seed = seed.wrapping_add(1);
+        }
+    }
+
+    /// Returns the value of the `content-type` header for this body.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary())
+    }
+
+    /// Encodes the body.
+    pub fn encode(&self) -> Vec<u8> {
+        let boundary = self.boundary();
+        let mut buf = Vec::new();
+
+        for part in &self.parts {
+            buf.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+            part.write_headers(&mut buf);
+            buf.extend_from_slice(b"\r\n");
+            buf.extend_from_slice(&part.data);
+            buf.extend_from_slice(b"\r\n");
+        }
+        buf.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        buf
+    }
+}
+
+impl Part {
+    /// Writes the headers of the part, each terminated by CRLF.
+    fn write_headers(&self, buf: &mut Vec<u8>) {
+        let mut disposition = format!(
+            "Content-Disposition: form-data; name=\"{}\"",
+            escape_quoted(&self.name)
+        );
+        if let Some(ref filename) = self.filename {
+            disposition.push_str(&format!("; filename=\"{}\"", escape_quoted(filename)));
+        }
+        buf.extend_from_slice(disposition.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+
+        if let Some(ref content_type) = self.content_type {
+            // Line breaks would allow injecting further headers or body content.
+            let content_type = content_type.replace(['\r', '\n'], " ");
+            buf.extend_from_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+        }
+    }
+}
+
+/// Escapes a value for use in a quoted header parameter.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Checks whether `needle` occurs in `haystack`.
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
 }
 
 impl HttpRequest {
-    /// Renders the headers as a string.
+    /// Renders the headers as a string, redacting secret headers.
     ///
-    /// The returned string is suitable for use in an HTTP request unaltered. Does not include the
-    /// `Host` header.
+    /// Does not include the `Host` header. Since secrets are redacted, the result is meant for
+    /// display only, see [`HttpRequest::all_headers`] for the headers to send.
     pub fn render_headers(&self) -> String {
-        self.headers
-            .iter()
-            .map(|(k, v)| format!("{}: {}", k, v.as_ref()))
+        self.redacted_headers()
+            .map(|(k, v)| format!("{}: {}", k, v))
             .collect::<Vec<_>>()
             .join("\n")
     }
-}
 
-impl fmt::Display for HttpRequest {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{} {} HTTP/1.1", self.method, self.path)?;
+    /// Renders the entire request like [`std::fmt::Display`], but without redacting secrets.
+    ///
+    /// Beware that the result contains credentials and should not be logged.
+    pub fn render_unredacted(&self) -> String {
+        let mut rendered = String::new();
+        self.write_to(&mut rendered, false)
+            .expect("writing to a string cannot fail");
+        rendered
+    }
+
+    /// Returns whether the value of the given header is redacted when printing.
+    pub fn is_secret_header(&self, name: &str) -> bool {
+        self.secret_headers
+            .iter()
+            .any(|secret| secret.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns all headers like [`HttpRequest::all_headers`], with secret values redacted.
+    fn redacted_headers(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        self.all_headers().map(|(k, v)| {
+            if self.is_secret_header(k) {
+                (k, Cow::Borrowed(REDACTED))
+            } else {
+                (k, v)
+            }
+        })
+    }
 
-        writeln!(f, "Host: {}", self.host)?;
-        for (key, value) in &self.headers {
-            writeln!(f, "{}: {}", key, value.as_ref())?;
+    /// Writes the request in HTTP/1.1 format, optionally redacting secret headers.
+    fn write_to<W: fmt::Write>(&self, w: &mut W, redact: bool) -> fmt::Result {
+        writeln!(w, "{} {} HTTP/1.1", self.method, self.path_and_query())?;
+
+        writeln!(w, "Host: {}", self.host)?;
+        if redact {
+            for (key, value) in self.redacted_headers() {
+                writeln!(w, "{}: {}", key, value)?;
+            }
+        } else {
+            for (key, value) in self.all_headers() {
+                writeln!(w, "{}: {}", key, value)?;
+            }
         }
 
         // Empty line between headers and body
-        writeln!(f)?;
+        writeln!(w)?;
 
         // Write body
-        write!(f, "{}", self.body)?;
+        match self.body {
+            Body::Empty => {}
+            Body::Json(ref json) => write!(w, "{}", json)?,
+            Body::Bytes { ref data, .. } => write!(w, "<{} bytes>", data.len())?,
+            Body::Multipart(ref multipart) => write!(w, "{}", multipart)?,
+        }
 
         Ok(())
     }
+
+    /// Returns all headers to be sent, including the `content-type` derived from the body.
+    ///
+    /// The derived content type comes first and is omitted if the headers already contain one.
+    pub fn all_headers(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        let content_type = self
+            .body
+            .content_type()
+            .filter(|_| {
+                !self
+                    .headers
+                    .iter()
+                    .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            })
+            .map(|content_type| ("content-type", content_type));
+
+        content_type.into_iter().chain(
+            self.headers
+                .iter()
+                .map(|(k, v)| (k.as_ref(), Cow::Borrowed(v.as_ref()))),
+        )
+    }
+
+    /// Renders the query string, percent-encoding keys and values.
+    ///
+    /// Returns an empty string if there are no query parameters, otherwise the leading `?` is
+    /// included.
+    pub fn query_string(&self) -> String {
+        let mut rendered = String::new();
+        for (key, value) in &self.query {
+            rendered.push(if rendered.is_empty() { '?' } else { '&' });
+            percent_encode(&mut rendered, key);
+            rendered.push('=');
+            percent_encode(&mut rendered, value);
+        }
+        rendered
+    }
+
+    /// Returns the path including the query string.
+    pub fn path_and_query(&self) -> String {
+        format!("{}{}", self.path, self.query_string())
+    }
+
+    /// Returns the full URL of the request.
+    pub fn url(&self) -> String {
+        format!("{}://{}{}", self.scheme, self.host, self.path_and_query())
+    }
+}
+
+/// Base URL of the API.
+///
+/// Consists of a scheme, a host with an optional port and an optional path prefix. Can be parsed
+/// from a string:
+///
+/// ```
+/// # use claus::http_request::BaseUrl;
+/// let base_url: BaseUrl = "http://localhost:8080/anthropic/".parse().unwrap();
+/// assert_eq!(base_url.scheme(), "http");
+/// assert_eq!(base_url.host(), "localhost:8080");
+/// assert_eq!(base_url.path_prefix(), "/anthropic");
+/// assert_eq!(base_url.to_string(), "http://localhost:8080/anthropic");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BaseUrl {
+    /// URL scheme, `http` or `https`.
+    scheme: String,
+    /// Host, including the port if given.
+    host: String,
+    /// Path prefix without trailing slash, empty if none.
+    path_prefix: String,
+}
+
+/// Error parsing a [`BaseUrl`].
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidBaseUrl {
+    /// The URL does not start with `http://` or `https://`.
+    #[error("base URL must start with http:// or https://")]
+    InvalidScheme,
+    /// The URL has no host.
+    #[error("base URL has no host")]
+    MissingHost,
+    /// The URL contains a query string or fragment.
+    #[error("base URL must not contain a query string or fragment")]
+    UnexpectedQuery,
+}
+
+impl BaseUrl {
+    /// Creates a base URL for the given host using HTTPS, without path prefix.
+    pub fn https<S: Into<String>>(host: S) -> Self {
+        Self {
+            scheme: "https".to_string(),
+            host: host.into(),
+            path_prefix: String::new(),
+        }
+    }
+
+    /// Returns the URL scheme.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the host, including the port if given.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the path prefix, without trailing slash.
+    pub fn path_prefix(&self) -> &str {
+        &self.path_prefix
+    }
+}
+
+impl std::str::FromStr for BaseUrl {
+    type Err = InvalidBaseUrl;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = url.split_once("://").ok_or(InvalidBaseUrl::InvalidScheme)?;
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme != "http" && scheme != "https" {
+            return Err(InvalidBaseUrl::InvalidScheme);
+        }
+
+        if rest.contains(['?', '#']) {
+            return Err(InvalidBaseUrl::UnexpectedQuery);
+        }
+
+        let (host, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        if host.is_empty() {
+            return Err(InvalidBaseUrl::MissingHost);
+        }
+
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            path_prefix: path.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+impl fmt::Display for BaseUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}{}", self.scheme, self.host, self.path_prefix)
+    }
+}
+
+/// Appends `value` to `buf`, percent-encoding all but unreserved characters.
+fn percent_encode(buf: &mut String, value: &str) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            buf.push(byte as char);
+        } else {
+            buf.push_str(&format!("%{byte:02X}"));
+        }
+    }
+}
+
+/// Percent-encodes a caller-supplied ID for use as a single path segment.
+///
+/// Slashes and other reserved characters are encoded, so the ID cannot change the endpoint.
+///
+/// # Panics
+///
+/// Panics if `id` is empty, `.` or `..`. URL parsers treat these as (possibly encoded) dot
+/// segments, so they cannot be expressed as a path segment.
+pub(crate) fn path_segment(id: &str) -> String {
+    assert!(
+        !matches!(id, "" | "." | ".."),
+        "invalid ID {id:?}, must not be empty, `.` or `..`"
+    );
+
+    let mut segment = String::with_capacity(id.len());
+    percent_encode(&mut segment, id);
+    segment
+}
+
+impl fmt::Display for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, true)
+    }
+}
+
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(k, v)| {
+                if self.is_secret_header(k) {
+                    (k.as_ref(), REDACTED)
+                } else {
+                    (k.as_ref(), v.as_ref())
+                }
+            })
+            .collect();
+
+        f.debug_struct("HttpRequest")
+            .field("scheme", &self.scheme)
+            .field("host", &self.host)
+            .field("path", &self.path)
+            .field("query", &self.query)
+            .field("method", &self.method)
+            .field("headers", &headers)
+            .field("secret_headers", &self.secret_headers)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Body::Empty => f.write_str("Empty"),
+            Body::Json(json) => f.debug_tuple("Json").field(json).finish(),
+            Body::Bytes { content_type, data } => f
+                .debug_struct("Bytes")
+                .field("content_type", content_type)
+                .field("data", &format_args!("<{} bytes>", data.len()))
+                .finish(),
+            Body::Multipart(multipart) => f.debug_tuple("Multipart").field(multipart).finish(),
+        }
+    }
+}
+
+impl fmt::Debug for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Part")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("data", &format_args!("<{} bytes>", self.data.len()))
+            .finish()
+    }
+}
+
+impl fmt::Display for Multipart {
+    /// Formats the body like [`Multipart::encode`], replacing binary data with a placeholder.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let boundary = self.boundary();
+
+        for part in &self.parts {
+            writeln!(f, "--{boundary}")?;
+            let mut headers = Vec::new();
+            part.write_headers(&mut headers);
+            write!(
+                f,
+                "{}",
+                String::from_utf8_lossy(&headers).replace("\r\n", "\n")
+            )?;
+            writeln!(f)?;
+            match std::str::from_utf8(&part.data) {
+                Ok(text) if part.filename.is_none() => writeln!(f, "{text}")?,
+                _ => writeln!(f, "<{} bytes>", part.data.len())?,
+            }
+        }
+        write!(f, "--{boundary}--")
+    }
 }
 
 #[cfg(feature = "reqwest")]
 impl HttpRequest {
     /// Converts this [`HttpRequest`] into a [`reqwest::Request`].
-    pub fn try_into_reqwest(self) -> Result<reqwest::Request, Box<dyn std::error::Error>> {
+    pub fn try_into_reqwest(
+        self,
+    ) -> Result<reqwest::Request, Box<dyn std::error::Error + Send + Sync>> {
         let method = reqwest::Method::from_bytes(self.method.as_bytes())?;
 
-        let url_string = format!("https://{}{}", self.host, self.path);
+        let url_string = self.url();
         let url = reqwest::Url::parse(&url_string)?;
         let mut request = reqwest::Request::new(method, url);
 
-        *request.body_mut() = Some(self.body.into());
-
         let headers = request.headers_mut();
-        for (key, value) in self.headers {
+        for (key, value) in self.all_headers() {
             let header_name = reqwest::header::HeaderName::from_bytes(key.as_bytes())?;
             let header_value = reqwest::header::HeaderValue::from_str(&value)?;
             headers.insert(header_name, header_value);
         }
 
+        if !self.body.is_empty() {
+            *request.body_mut() = Some(self.body.into_bytes().into());
+        }
+
         Ok(request)
     }
 
@@ -98,17 +667,21 @@ impl HttpRequest {
     pub fn try_into_reqwest_builder(
         self,
         client: &reqwest::Client,
-    ) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error>> {
+    ) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
         let method = reqwest::Method::from_bytes(self.method.as_bytes())?;
-        let url_string = format!("https://{}{}", self.host, self.path);
+        let url_string = self.url();
 
-        let mut request_builder = client.request(method, &url_string).body(self.body);
+        let mut request_builder = client.request(method, &url_string);
 
         // Add headers
-        for (key, value) in self.headers {
+        for (key, value) in self.all_headers() {
             request_builder = request_builder.header(key, value.as_ref());
         }
 
+        if !self.body.is_empty() {
+            request_builder = request_builder.body(self.body.into_bytes());
+        }
+
         Ok(request_builder)
     }
 }
@@ -118,26 +691,45 @@ impl HttpRequest {
     /// Converts this [`HttpRequest`] into a [`reqwest::blocking::Request`].
     pub fn try_into_reqwest_blocking(
         self,
-    ) -> Result<reqwest::blocking::Request, Box<dyn std::error::Error>> {
+    ) -> Result<reqwest::blocking::Request, Box<dyn std::error::Error + Send + Sync>> {
         let method = reqwest::Method::from_bytes(self.method.as_bytes())?;
 
-        let url_string = format!("https://{}{}", self.host, self.path);
+        let url_string = self.url();
         let url = reqwest::Url::parse(&url_string)?;
         let mut request = reqwest::blocking::Request::new(method, url);
 
-        *request.body_mut() = Some(self.body.into());
-
         let headers = request.headers_mut();
-        for (key, value) in self.headers {
+        for (key, value) in self.all_headers() {
             let header_name = reqwest::header::HeaderName::from_bytes(key.as_bytes())?;
             let header_value = reqwest::header::HeaderValue::from_str(&value)?;
             headers.insert(header_name, header_value);
         }
 
+        if !self.body.is_empty() {
+            *request.body_mut() = Some(self.body.into_bytes().into());
+        }
+
         Ok(request)
     }
 }
 
+#[cfg(feature = "http")]
+impl TryFrom<HttpRequest> for http::Request<Vec<u8>> {
+    type Error = http::Error;
+
+    fn try_from(http_request: HttpRequest) -> Result<Self, Self::Error> {
+        let mut builder = http::Request::builder()
+            .method(http_request.method.as_ref())
+            .uri(http_request.url());
+
+        for (key, value) in http_request.all_headers() {
+            builder = builder.header(key, value.as_ref());
+        }
+
+        builder.body(http_request.body.into_bytes())
+    }
+}
+
 #[cfg(feature = "reqwest")]
 impl From<HttpRequest> for reqwest::Request {
     fn from(http_request: HttpRequest) -> Self {
@@ -158,27 +750,142 @@ impl From<HttpRequest> for reqwest::blocking::Request {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use super::{Body, DEFAULT_SECRET_HEADERS, HttpRequest, Multipart};
+
+    #[test]
+    fn test_query_and_body_rendering() {
+        let mut http_request = HttpRequest {
+            scheme: "https".to_string(),
+            host: "api.anthropic.com".to_string(),
+            path: "/v1/models".to_string(),
+            query: vec![
+                ("after_id".to_string(), "claude 3".to_string()),
+                ("limit".to_string(), "5".to_string()),
+            ],
+            method: "GET".into(),
+            headers: vec![("x-api-key".into(), Arc::from("test-key"))],
+            secret_headers: DEFAULT_SECRET_HEADERS.into(),
+            body: Body::Empty,
+        };
+
+        assert_eq!(
+            http_request.path_and_query(),
+            "/v1/models?after_id=claude%203&limit=5"
+        );
+        assert_eq!(http_request.render_headers(), "x-api-key: <redacted>");
+        assert_eq!(
+            http_request.to_string(),
+            "GET /v1/models?after_id=claude%203&limit=5 HTTP/1.1\n\
+             Host: api.anthropic.com\n\
+             x-api-key: <redacted>\n\
+             \n"
+        );
+
+        http_request.method = "PUT".into();
+        http_request.body = vec![0xde, 0xad].into();
+        assert_eq!(
+            http_request.render_headers(),
+            "content-type: application/octet-stream\nx-api-key: <redacted>"
+        );
+        assert!(http_request.to_string().ends_with("\n\n<2 bytes>"));
+        assert!(format!("{http_request:?}").contains("data: <2 bytes>"));
+
+        http_request.body = Multipart::new()
+            .file("file", "a.bin", "application/octet-stream", vec![0; 64])
+            .into();
+        let debug = format!("{http_request:?}");
+        assert!(debug.contains("data: <64 bytes>"));
+        assert!(!debug.contains("[0, 0"));
+
+        // An explicit content type replaces the derived one, regardless of case.
+        http_request
+            .headers
+            .push(("Content-Type".into(), Arc::from("image/png")));
+        assert_eq!(
+            http_request.render_headers(),
+            "x-api-key: <redacted>\nContent-Type: image/png"
+        );
+    }
+
+    #[test]
+    fn test_multipart_header_injection() {
+        let multipart = Multipart::new().file(
+            "file\r\nX-Injected: 1",
+            "a.txt",
+            "text/plain\r\n\r\ninjected",
+            b"data".to_vec(),
+        );
+        let encoded = String::from_utf8(multipart.encode()).unwrap();
+
+        assert!(encoded.contains("name=\"file  X-Injected: 1\""));
+        assert!(encoded.contains("Content-Type: text/plain    injected\r\n\r\ndata\r\n"));
+    }
+
+    #[test]
+    fn test_secret_header_redaction() {
+        let mut http_request = HttpRequest {
+            scheme: "https".to_string(),
+            host: "api.anthropic.com".to_string(),
+            path: "/v1/models".to_string(),
+            query: Vec::new(),
+            method: "GET".into(),
+            headers: vec![
+                ("x-api-key".into(), Arc::from("sk-ant-secret")),
+                ("x-gateway-token".into(), Arc::from("gw-secret")),
+            ],
+            secret_headers: DEFAULT_SECRET_HEADERS.into(),
+            body: Body::Empty,
+        };
+
+        assert!(!format!("{http_request:?}").contains("sk-ant-secret"));
+        assert!(
+            http_request
+                .render_unredacted()
+                .contains("x-api-key: sk-ant-secret\n")
+        );
+
+        // The list of secret headers is configurable.
+        http_request.secret_headers = Arc::from(["X-Gateway-Token".into()]);
+        assert_eq!(
+            http_request.render_headers(),
+            "x-api-key: sk-ant-secret\nx-gateway-token: <redacted>"
+        );
+        assert!(!http_request.to_string().contains("gw-secret"));
+        assert!(!format!("{http_request:?}").contains("gw-secret"));
+    }
 
     #[cfg(feature = "reqwest")]
     #[test]
     fn test_http_request_to_reqwest_conversion() {
         let http_request = super::HttpRequest {
+            scheme: "https".to_string(),
             host: "api.anthropic.com".to_string(),
             path: "/v1/messages".to_string(),
-            method: "POST",
+            query: Vec::new(),
+            method: "POST".into(),
             headers: vec![
-                ("content-type", std::sync::Arc::from("application/json")),
-                ("anthropic-version", std::sync::Arc::from("2023-06-01")),
-                ("x-api-key", std::sync::Arc::from("test-key")),
                 (
-                    "anthropic-model",
+                    "content-type".into(),
+                    std::sync::Arc::from("application/json"),
+                ),
+                (
+                    "anthropic-version".into(),
+                    std::sync::Arc::from("2023-06-01"),
+                ),
+                ("x-api-key".into(), std::sync::Arc::from("test-key")),
+                (
+                    "anthropic-model".into(),
                     std::sync::Arc::from("claude-3-sonnet-20240229"),
                 ),
-                ("max-tokens", std::sync::Arc::from("1024")),
+                ("max-tokens".into(), std::sync::Arc::from("1024")),
             ],
+            secret_headers: super::DEFAULT_SECRET_HEADERS.into(),
             body:
                 r#"{"messages":[{"role":"user","content":{"type":"text","text":"Hello, world!"}}]}"#
-                    .to_string(),
+                    .to_string()
+                    .into(),
         };
 
         // Convert to reqwest::Request
@@ -206,22 +913,32 @@ mod tests {
     #[test]
     fn test_http_request_to_reqwest_blocking_conversion() {
         let http_request = super::HttpRequest {
+            scheme: "https".to_string(),
             host: "api.anthropic.com".to_string(),
             path: "/v1/messages".to_string(),
-            method: "POST",
+            query: Vec::new(),
+            method: "POST".into(),
             headers: vec![
-                ("content-type", std::sync::Arc::from("application/json")),
-                ("anthropic-version", std::sync::Arc::from("2023-06-01")),
-                ("x-api-key", std::sync::Arc::from("test-key")),
                 (
-                    "anthropic-model",
+                    "content-type".into(),
+                    std::sync::Arc::from("application/json"),
+                ),
+                (
+                    "anthropic-version".into(),
+                    std::sync::Arc::from("2023-06-01"),
+                ),
+                ("x-api-key".into(), std::sync::Arc::from("test-key")),
+                (
+                    "anthropic-model".into(),
                     std::sync::Arc::from("claude-3-sonnet-20240229"),
                 ),
-                ("max-tokens", std::sync::Arc::from("1024")),
+                ("max-tokens".into(), std::sync::Arc::from("1024")),
             ],
+            secret_headers: super::DEFAULT_SECRET_HEADERS.into(),
             body:
                 r#"{"messages":[{"role":"user","content":{"type":"text","text":"Hello, world!"}}]}"#
-                    .to_string(),
+                    .to_string()
+                    .into(),
         };
 
         // Convert to reqwest::blocking::Request
@@ -249,22 +966,32 @@ mod tests {
     #[test]
     fn test_http_request_to_reqwest_request_builder() {
         let http_request = super::HttpRequest {
+            scheme: "https".to_string(),
             host: "api.anthropic.com".to_string(),
             path: "/v1/messages".to_string(),
-            method: "POST",
+            query: Vec::new(),
+            method: "POST".into(),
             headers: vec![
-                ("content-type", std::sync::Arc::from("application/json")),
-                ("anthropic-version", std::sync::Arc::from("2023-06-01")),
-                ("x-api-key", std::sync::Arc::from("test-key")),
                 (
-                    "anthropic-model",
+                    "content-type".into(),
+                    std::sync::Arc::from("application/json"),
+                ),
+                (
+                    "anthropic-version".into(),
+                    std::sync::Arc::from("2023-06-01"),
+                ),
+                ("x-api-key".into(), std::sync::Arc::from("test-key")),
+                (
+                    "anthropic-model".into(),
                     std::sync::Arc::from("claude-3-sonnet-20240229"),
                 ),
-                ("max-tokens", std::sync::Arc::from("1024")),
+                ("max-tokens".into(), std::sync::Arc::from("1024")),
             ],
+            secret_headers: super::DEFAULT_SECRET_HEADERS.into(),
             body:
                 r#"{"messages":[{"role":"user","content":{"type":"text","text":"Hello, world!"}}]}"#
-                    .to_string(),
+                    .to_string()
+                    .into(),
         };
 
         let client = reqwest::Client::new();
@@ -294,4 +1021,109 @@ mod tests {
         assert!(body_str.contains("Hello, world!"));
         assert!(body_str.contains("\"type\":\"text\""));
     }
+
+    #[test]
+    fn test_base_url_parsing() {
+        use super::{BaseUrl, InvalidBaseUrl};
+
+        let base_url: BaseUrl = "https://api.anthropic.com".parse().unwrap();
+        assert_eq!(base_url, BaseUrl::https("api.anthropic.com"));
+
+        let base_url: BaseUrl = "HTTP://127.0.0.1:8080/".parse().unwrap();
+        assert_eq!(base_url.scheme(), "http");
+        assert_eq!(base_url.host(), "127.0.0.1:8080");
+        assert_eq!(base_url.path_prefix(), "");
+
+        let base_url: BaseUrl = "https://gateway.example.com/llm/anthropic/"
+            .parse()
+            .unwrap();
+        assert_eq!(base_url.path_prefix(), "/llm/anthropic");
+
+        assert!(matches!(
+            "api.anthropic.com".parse::<BaseUrl>(),
+            Err(InvalidBaseUrl::InvalidScheme)
+        ));
+        assert!(matches!(
+            "ftp://api.anthropic.com".parse::<BaseUrl>(),
+            Err(InvalidBaseUrl::InvalidScheme)
+        ));
+        assert!(matches!(
+            "https:///v1".parse::<BaseUrl>(),
+            Err(InvalidBaseUrl::MissingHost)
+        ));
+        assert!(matches!(
+            "https://example.com/?key=value".parse::<BaseUrl>(),
+            Err(InvalidBaseUrl::UnexpectedQuery)
+        ));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_base_url_to_reqwest_conversion() {
+        let api =
+            crate::Api::new("test-key").base_url("http://127.0.0.1:8080/proxy".parse().unwrap());
+
+        let http_request = crate::models::ListModelsRequestBuilder::new()
+            .limit(1)
+            .build(&api);
+        assert_eq!(http_request.host, "127.0.0.1:8080");
+        assert_eq!(http_request.path, "/proxy/v1/models");
+        assert!(http_request.to_string().contains("Host: 127.0.0.1:8080\n"));
+
+        let reqwest_request: reqwest::Request = http_request.into();
+        assert_eq!(
+            reqwest_request.url().as_str(),
+            "http://127.0.0.1:8080/proxy/v1/models?limit=1"
+        );
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_get_request_to_reqwest_conversion() {
+        let http_request = HttpRequest {
+            scheme: "https".to_string(),
+            host: "api.anthropic.com".to_string(),
+            path: "/v1/files".to_string(),
+            query: vec![("limit".to_string(), "5".to_string())],
+            method: "GET".into(),
+            headers: vec![("x-api-key".into(), Arc::from("test-key"))],
+            secret_headers: DEFAULT_SECRET_HEADERS.into(),
+            body: Body::Empty,
+        };
+
+        let reqwest_request: reqwest::Request = http_request.into();
+
+        assert_eq!(reqwest_request.method(), &reqwest::Method::GET);
+        assert_eq!(
+            reqwest_request.url().as_str(),
+            "https://api.anthropic.com/v1/files?limit=5"
+        );
+        assert!(reqwest_request.headers().get("content-type").is_none());
+        assert!(reqwest_request.body().is_none());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_request_to_http_conversion() {
+        let api = crate::Api::new("test-key");
+        let http_request = crate::MessagesRequestBuilder::new()
+            .push_message(crate::anthropic::Role::User, "Hello, world!")
+            .build(&api);
+
+        let request = http::Request::<Vec<u8>>::try_from(http_request).unwrap();
+
+        assert_eq!(request.method(), http::Method::POST);
+        assert_eq!(request.uri(), "https://api.anthropic.com/v1/messages");
+        assert_eq!(request.headers()["content-type"], "application/json");
+        assert_eq!(request.headers()["x-api-key"], "test-key");
+        assert!(
+            std::str::from_utf8(request.body())
+                .unwrap()
+                .contains("Hello, world!")
+        );
+
+        let mut http_request = crate::files::delete(&api, "file_01");
+        http_request.method = "NOT A METHOD".into();
+        assert!(http::Request::<Vec<u8>>::try_from(http_request).is_err());
+    }
 }
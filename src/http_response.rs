@@ -0,0 +1,315 @@
+//! Abstract HTTP response.
+//!
+//! The [`HttpResponse`] type represents a response received from the Anthropic API, without
+//! committing to a specific HTTP client. Unlike a bare body, it retains the status code and
+//! headers, which carry the request ID and rate limit information. Use
+//! [`crate::parse_response`] to turn it into a typed response.
+//!
+//! Rate limit information can be extracted using [`HttpResponse::rate_limit`].
+//!
+//! ## Features
+//!
+//! If the `reqwest`/`reqwest-blocking` feature is enabled, an [`HttpResponse`] can be created
+//! from a [`reqwest::Response`] or [`reqwest::blocking::Response`] using the `try_from_reqwest`
+//! or `try_from_reqwest_blocking` methods.
+//!
+//! If the `http` feature is enabled, an [`HttpResponse`] can be created from any
+//! `http::Response` whose body can be converted into bytes.
+
+use std::time::Duration;
+
+/// HTTP response encapsulation.
+///
+/// Header names are compared case-insensitively by [`HttpResponse::header`].
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers.
+    pub headers: Vec<(String, String)>,
+    /// Response body.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a new response from its parts.
+    pub fn new<B: Into<Vec<u8>>>(status: u16, headers: Vec<(String, String)>, body: B) -> Self {
+        Self {
+            status,
+            headers,
+            body: body.into(),
+        }
+    }
+
+    /// Returns whether the status code indicates success (2xx).
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Returns the value of the first header with the given name, if any.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the ID the API assigned to the request, from the `request-id` header.
+    pub fn request_id(&self) -> Option<&str> {
+        self.header("request-id")
+    }
+
+    /// Returns the body as text, replacing invalid UTF-8.
+    pub fn body_text(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Parses the rate limit headers of the response.
+    pub fn rate_limit(&self) -> RateLimitInfo {
+        RateLimitInfo::from_response(self)
+    }
+
+    /// Returns how long to wait before retrying, as requested by the API.
+    ///
+    /// Parses the `retry-after-ms` and `retry-after` headers, the latter only if given in seconds.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.header("retry-after-ms")
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .and_then(|millis| Duration::try_from_secs_f64(millis / 1000.0).ok())
+            .or_else(|| {
+                self.header("retry-after")
+                    .and_then(|value| value.trim().parse::<f64>().ok())
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            })
+    }
+}
+
+/// A single rate limit, e.g. for requests per minute.
+///
+/// See <https://docs.anthropic.com/en/api/rate-limits#response-headers> for details.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RateLimit {
+    /// Maximum allowed within the rate limit window.
+    pub limit: Option<u64>,
+    /// Remaining before being rate limited.
+    pub remaining: Option<u64>,
+    /// RFC 3339 timestamp at which the limit will be fully replenished.
+    pub reset: Option<String>,
+}
+
+impl RateLimit {
+    /// Parses the `anthropic-ratelimit-{name}-*` headers.
+    fn from_response(response: &HttpResponse, name: &str) -> Self {
+        let header =
+            |suffix: &str| response.header(&format!("anthropic-ratelimit-{name}-{suffix}"));
+
+        Self {
+            limit: header("limit").and_then(|value| value.trim().parse().ok()),
+            remaining: header("remaining").and_then(|value| value.trim().parse().ok()),
+            reset: header("reset").map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// Rate limit information returned with every API response.
+///
+/// Fields are left empty if the API did not send the respective headers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RateLimitInfo {
+    /// Requests limit.
+    pub requests: RateLimit,
+    /// Combined token limit, the most restrictive of input and output tokens.
+    pub tokens: RateLimit,
+    /// Input tokens limit.
+    pub input_tokens: RateLimit,
+    /// Output tokens limit.
+    pub output_tokens: RateLimit,
+    /// How long to wait before retrying, see [`HttpResponse::retry_after`].
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Parses rate limit information from the headers of a response.
+    pub fn from_response(response: &HttpResponse) -> Self {
+        Self {
+            requests: RateLimit::from_response(response, "requests"),
+            tokens: RateLimit::from_response(response, "tokens"),
+            input_tokens: RateLimit::from_response(response, "input-tokens"),
+            output_tokens: RateLimit::from_response(response, "output-tokens"),
+            retry_after: response.retry_after(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl<B> From<http::Response<B>> for HttpResponse
+where
+    B: Into<Vec<u8>>,
+{
+    /// Converts an `http::Response`, skipping headers whose values are not valid text.
+    fn from(response: http::Response<B>) -> Self {
+        let (parts, body) = response.into_parts();
+
+        let headers = parts
+            .headers
+            .iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+
+        Self {
+            status: parts.status.as_u16(),
+            headers,
+            body: body.into(),
+        }
+    }
+}
+
+/// Collects headers from a [`reqwest::header::HeaderMap`], skipping non-text values.
+#[cfg(feature = "reqwest")]
+fn collect_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpResponse {
+    /// Creates an [`HttpResponse`] from a [`reqwest::Response`], reading the entire body.
+    ///
+    /// Not suitable for streaming responses, which should be read chunk by chunk instead.
+    pub async fn try_from_reqwest(response: reqwest::Response) -> Result<Self, reqwest::Error> {
+        let status = response.status().as_u16();
+        let headers = collect_headers(response.headers());
+        let body = response.bytes().await?.to_vec();
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+#[cfg(feature = "reqwest-blocking")]
+impl HttpResponse {
+    /// Creates an [`HttpResponse`] from a [`reqwest::blocking::Response`], reading the entire
+    /// body.
+    pub fn try_from_reqwest_blocking(
+        response: reqwest::blocking::Response,
+    ) -> Result<Self, reqwest::Error> {
+        let status = response.status().as_u16();
+        let headers = collect_headers(response.headers());
+        let body = response.bytes()?.to_vec();
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+#[cfg(feature = "reqwest-blocking")]
+impl TryFrom<reqwest::blocking::Response> for HttpResponse {
+    type Error = reqwest::Error;
+
+    fn try_from(response: reqwest::blocking::Response) -> Result<Self, Self::Error> {
+        Self::try_from_reqwest_blocking(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::HttpResponse;
+
+    #[test]
+    fn test_header_lookup() {
+        let response = HttpResponse::new(
+            200,
+            vec![
+                ("Request-Id".to_string(), "req_01".to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            "{}",
+        );
+
+        assert!(response.is_success());
+        assert_eq!(response.request_id(), Some("req_01"));
+        assert_eq!(response.header("Content-Type"), Some("application/json"));
+        assert_eq!(response.header("retry-after"), None);
+    }
+
+    #[test]
+    fn test_rate_limit_info() {
+        let headers = [
+            ("anthropic-ratelimit-requests-limit", "50"),
+            ("anthropic-ratelimit-requests-remaining", "0"),
+            ("anthropic-ratelimit-requests-reset", "2025-01-01T00:00:30Z"),
+            ("anthropic-ratelimit-input-tokens-limit", "40000"),
+            ("anthropic-ratelimit-input-tokens-remaining", "39000"),
+            ("retry-after", "30"),
+        ];
+        let response = HttpResponse::new(
+            429,
+            headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            "",
+        );
+
+        let info = response.rate_limit();
+        assert_eq!(info.requests.limit, Some(50));
+        assert_eq!(info.requests.remaining, Some(0));
+        assert_eq!(info.requests.reset.as_deref(), Some("2025-01-01T00:00:30Z"));
+        assert_eq!(info.input_tokens.remaining, Some(39000));
+        assert_eq!(info.tokens.limit, None);
+        assert_eq!(info.retry_after, Some(Duration::from_secs(30)));
+
+        let response = HttpResponse::new(
+            529,
+            vec![
+                ("retry-after".to_string(), "30".to_string()),
+                ("retry-after-ms".to_string(), "1500".to_string()),
+            ],
+            "",
+        );
+        assert_eq!(response.retry_after(), Some(Duration::from_millis(1500)));
+
+        // Invalid millisecond values fall back to the seconds header.
+        for millis in ["-1500", "NaN", "inf"] {
+            let response = HttpResponse::new(
+                429,
+                vec![
+                    ("retry-after".to_string(), "30".to_string()),
+                    ("retry-after-ms".to_string(), millis.to_string()),
+                ],
+                "",
+            );
+            assert_eq!(response.retry_after(), Some(Duration::from_secs(30)));
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_response_conversion() {
+        let response = http::Response::builder()
+            .status(429)
+            .header("request-id", "req_01")
+            .header("retry-after", "5")
+            .body(r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Slow down"}}"#)
+            .unwrap();
+
+        let response = HttpResponse::from(response);
+        assert_eq!(response.status, 429);
+        assert_eq!(response.request_id(), Some("req_01"));
+        assert_eq!(response.retry_after(), Some(Duration::from_secs(5)));
+
+        let result: Result<crate::anthropic::MessagesResponse, _> =
+            crate::parse_response(&response);
+        assert!(matches!(result, Err(crate::ResponseError::Api(_))));
+    }
+}
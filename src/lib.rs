@@ -5,43 +5,191 @@ pub use im;
 pub use schemars;
 
 pub mod anthropic;
+pub mod batches;
 pub mod claudio;
+pub mod client;
 pub mod conversation;
+pub mod files;
 pub mod http_request;
+pub mod http_response;
+pub mod models;
+pub mod retry;
+pub mod streaming;
 
-use std::sync::Arc;
+use std::{borrow::Cow, fmt, marker::PhantomData, sync::Arc};
 
-use crate::{anthropic::ApiResponse, http_request::HttpRequest};
+use crate::{
+    anthropic::ApiResponse,
+    http_request::{BaseUrl, Body, DEFAULT_SECRET_HEADERS, HttpRequest},
+};
 
 /// A client for the Anthropic API.
 ///
 /// The [`Api`] struct holds configuration necessary to make API requests. Create one using
 /// [`Api::new`] with your API key, then use it to build requests with types like
 /// [`MessagesRequestBuilder`].
-#[derive(Clone, Debug)]
+///
+/// Credentials are redacted when debug printing.
+#[derive(Clone)]
 pub struct Api {
-    /// The Anthropic API key.
-    api_key: Arc<str>,
+    /// How requests are authenticated.
+    auth: Auth,
     /// The default model to use for requests.
     default_model: Arc<str>,
     /// The default maximum number of tokens for responses.
     default_max_tokens: u32,
-    /// The API endpoint host (without protocol or path).
-    endpoint_host: Arc<str>,
+    /// The base URL all request paths are relative to.
+    base_url: Arc<BaseUrl>,
+    /// The default sampling temperature.
+    default_temperature: Option<f32>,
+    /// The default top K sampling cut-off.
+    default_top_k: Option<u32>,
+    /// The default nucleus sampling cut-off.
+    default_top_p: Option<f32>,
+    /// The default stop sequences.
+    default_stop_sequences: Option<Arc<[String]>>,
+    /// The default request metadata.
+    default_metadata: Option<Arc<anthropic::Metadata>>,
+    /// The default service tier.
+    default_service_tier: Option<anthropic::ServiceTier>,
+    /// Beta features enabled for all requests.
+    betas: Arc<[anthropic::Beta]>,
+    /// Additional headers sent with all requests.
+    extra_headers: Arc<[(Cow<'static, str>, Arc<str>)]>,
+    /// Headers redacted when printing requests.
+    secret_headers: Arc<[Cow<'static, str>]>,
+}
+
+impl fmt::Debug for Api {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let extra_headers: Vec<_> = self
+            .extra_headers
+            .iter()
+            .map(|(k, v)| {
+                if self.is_secret_header(k) {
+                    (k.as_ref(), http_request::REDACTED)
+                } else {
+                    (k.as_ref(), v.as_ref())
+                }
+            })
+            .collect();
+
+        f.debug_struct("Api")
+            .field("auth", &self.auth)
+            .field("default_model", &self.default_model)
+            .field("default_max_tokens", &self.default_max_tokens)
+            .field("base_url", &self.base_url)
+            .field("default_temperature", &self.default_temperature)
+            .field("default_top_k", &self.default_top_k)
+            .field("default_top_p", &self.default_top_p)
+            .field("default_stop_sequences", &self.default_stop_sequences)
+            .field("default_metadata", &self.default_metadata)
+            .field("default_service_tier", &self.default_service_tier)
+            .field("betas", &self.betas)
+            .field("extra_headers", &extra_headers)
+            .field("secret_headers", &self.secret_headers)
+            .finish()
+    }
+}
+
+/// Authentication strategy for API requests.
+///
+/// Values are redacted when debug printing.
+#[derive(Clone)]
+pub enum Auth {
+    /// Anthropic API key, sent in the `x-api-key` header.
+    ApiKey(Arc<str>),
+    /// Token sent in an `Authorization: Bearer` header, e.g. for OAuth or gateways.
+    BearerToken(Arc<str>),
+    /// Custom header, e.g. for proxies using their own authentication.
+    ///
+    /// The header is redacted when printing requests.
+    Header {
+        /// Header name.
+        name: Cow<'static, str>,
+        /// Header value.
+        value: Arc<str>,
+    },
+    /// No authentication, e.g. for proxies adding credentials themselves.
+    None,
+}
+
+impl Auth {
+    /// Returns the header carrying the credentials, if any.
+    fn header(&self) -> Option<(Cow<'static, str>, Arc<str>)> {
+        match self {
+            Auth::ApiKey(api_key) => Some(("x-api-key".into(), api_key.clone())),
+            Auth::BearerToken(token) => {
+                Some(("authorization".into(), Arc::from(format!("Bearer {token}"))))
+            }
+            Auth::Header { name, value } => Some((name.clone(), value.clone())),
+            Auth::None => None,
+        }
+    }
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::ApiKey(_) => f.debug_tuple("ApiKey").field(&"<redacted>").finish(),
+            Auth::BearerToken(_) => f.debug_tuple("BearerToken").field(&"<redacted>").finish(),
+            Auth::Header { name, .. } => f
+                .debug_struct("Header")
+                .field("name", name)
+                .field("value", &"<redacted>")
+                .finish(),
+            Auth::None => f.write_str("None"),
+        }
+    }
 }
 
 impl Api {
     /// Creates a new [`Api`] client with the given API key.
     ///
     /// Requires a valid Anthropic API key. If you do not have one, you can get one from
-    /// [Anthropic's console](https://console.anthropic.com/settings/keys).
+    /// [Anthropic's console](https://console.anthropic.com/settings/keys). For other means of
+    /// authentication, see [`Api::with_auth`].
     pub fn new<S: Into<Arc<str>>>(api_key: S) -> Self {
+        Self::with_auth(Auth::ApiKey(api_key.into()))
+    }
+
+    /// Creates a new [`Api`] client with the given authentication strategy.
+    ///
+    /// ```
+    /// # use claus::{Api, Auth, MessagesRequestBuilder};
+    /// let api = Api::with_auth(Auth::BearerToken("my-token".into()));
+    ///
+    /// let http_req = MessagesRequestBuilder::new().build(&api);
+    /// assert!(http_req.render_unredacted().contains("authorization: Bearer my-token\n"));
+    /// ```
+    pub fn with_auth(auth: Auth) -> Self {
         Self {
-            api_key: api_key.into(),
+            auth: Auth::None,
             default_model: Arc::from(anthropic::DEFAULT_MODEL),
             default_max_tokens: 1024,
-            endpoint_host: Arc::from(anthropic::DEFAULT_ENDPOINT_HOST),
+            base_url: Arc::new(BaseUrl::https(anthropic::DEFAULT_ENDPOINT_HOST)),
+            default_temperature: None,
+            default_top_k: None,
+            default_top_p: None,
+            default_stop_sequences: None,
+            default_metadata: None,
+            default_service_tier: None,
+            betas: Arc::new([]),
+            extra_headers: Arc::new([]),
+            secret_headers: DEFAULT_SECRET_HEADERS.into(),
         }
+        .auth(auth)
+    }
+
+    /// Sets the authentication strategy.
+    ///
+    /// The header of [`Auth::Header`] is marked as secret, see [`Api::secret_header`].
+    pub fn auth(mut self, auth: Auth) -> Self {
+        if let Auth::Header { ref name, .. } = auth {
+            self = self.secret_header(name.clone());
+        }
+        self.auth = auth;
+        self
     }
 
     /// Sets the default model to use for requests.
@@ -63,23 +211,305 @@ impl Api {
         self
     }
 
+    /// Sets the default sampling temperature.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::temperature`]. If not set, the
+    /// API default is used.
+    pub fn default_temperature(mut self, temperature: f32) -> Self {
+        self.default_temperature = Some(temperature);
+        self
+    }
+
+    /// Sets the default top K sampling cut-off.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::top_k`].
+    pub fn default_top_k(mut self, top_k: u32) -> Self {
+        self.default_top_k = Some(top_k);
+        self
+    }
+
+    /// Sets the default nucleus sampling cut-off.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::top_p`].
+    pub fn default_top_p(mut self, top_p: f32) -> Self {
+        self.default_top_p = Some(top_p);
+        self
+    }
+
+    /// Sets the default stop sequences.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::stop_sequences`].
+    pub fn default_stop_sequences<I, S>(mut self, stop_sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default_stop_sequences = Some(stop_sequences.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the default request metadata.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::metadata`].
+    pub fn default_metadata(mut self, metadata: anthropic::Metadata) -> Self {
+        self.default_metadata = Some(Arc::new(metadata));
+        self
+    }
+
+    /// Sets the default service tier.
+    ///
+    /// Can be overridden per request, see [`MessagesRequestBuilder::service_tier`].
+    pub fn default_service_tier(mut self, service_tier: anthropic::ServiceTier) -> Self {
+        self.default_service_tier = Some(service_tier);
+        self
+    }
+
     /// Sets the API endpoint host.
     ///
-    /// This can only be a hostname, not a full URL.
+    /// This can only be a hostname (optionally with a port), requests will be sent using HTTPS.
+    /// Use [`Api::base_url`] to set a full URL instead.
     ///
     /// If not set, [`anthropic::DEFAULT_ENDPOINT_HOST`] will be used.
-    pub fn endpoint_host<S: Into<Arc<str>>>(mut self, endpoint_host: S) -> Self {
-        self.endpoint_host = endpoint_host.into();
+    pub fn endpoint_host<S: Into<String>>(self, endpoint_host: S) -> Self {
+        self.base_url(BaseUrl::https(endpoint_host))
+    }
+
+    /// Sets the base URL of the API.
+    ///
+    /// Allows sending requests to a mock server, gateway or proxy. All request paths are appended
+    /// to the base URL, e.g. `http://localhost:8080/anthropic` results in messages requests to
+    /// `http://localhost:8080/anthropic/v1/messages`.
+    ///
+    /// ```
+    /// # use claus::{Api, MessagesRequestBuilder, http_request::BaseUrl};
+    /// let base_url: BaseUrl = "http://127.0.0.1:8080/anthropic/".parse().unwrap();
+    /// let api = Api::new("sk-ant-api03-...").base_url(base_url);
+    ///
+    /// let http_req = MessagesRequestBuilder::new().build(&api);
+    /// assert_eq!(http_req.url(), "http://127.0.0.1:8080/anthropic/v1/messages");
+    /// ```
+    pub fn base_url(mut self, base_url: BaseUrl) -> Self {
+        self.base_url = Arc::new(base_url);
+        self
+    }
+
+    /// Enables a beta feature for all requests.
+    ///
+    /// Betas enabled here are merged with those enabled per request, see
+    /// [`MessagesRequestBuilder::beta`].
+    pub fn beta<B: Into<anthropic::Beta>>(mut self, beta: B) -> Self {
+        let beta = beta.into();
+        if !self.betas.contains(&beta) {
+            self.betas = self.betas.iter().cloned().chain([beta]).collect();
+        }
         self
     }
 
+    /// Adds a header that is sent with all requests.
+    ///
+    /// This is an escape hatch for features not supported by this crate. A header with the same
+    /// name set by the crate will be replaced. For `anthropic-beta`, use [`Api::beta`] instead.
+    pub fn extra_header<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<Arc<str>>,
+    {
+        self.extra_headers = self
+            .extra_headers
+            .iter()
+            .cloned()
+            .chain([(name.into(), value.into())])
+            .collect();
+        self
+    }
+
+    /// Marks a header as secret, redacting its value when printing requests.
+    ///
+    /// Useful if credentials are passed through [`Api::extra_header`], e.g. for a gateway. See
+    /// [`HttpRequest::secret_headers`] for details.
+    pub fn secret_header<N: Into<Cow<'static, str>>>(mut self, name: N) -> Self {
+        let name = name.into();
+        if !self.is_secret_header(&name) {
+            self.secret_headers = self.secret_headers.iter().cloned().chain([name]).collect();
+        }
+        self
+    }
+
+    /// Returns whether the value of the given header is redacted when printing.
+    fn is_secret_header(&self, name: &str) -> bool {
+        self.secret_headers
+            .iter()
+            .any(|secret| secret.eq_ignore_ascii_case(name))
+    }
+
     /// Creates the required headers for any API request.
-    fn create_default_headers(&self) -> Vec<(&'static str, Arc<str>)> {
-        vec![
-            ("content-type", Arc::from("application/json")),
-            ("anthropic-version", Arc::from(anthropic::ANTHROPIC_VERSION)),
-            ("x-api-key", self.api_key.clone()),
-        ]
+    fn create_default_headers(&self) -> Vec<(Cow<'static, str>, Arc<str>)> {
+        let mut headers = vec![(
+            Cow::Borrowed("anthropic-version"),
+            Arc::from(anthropic::ANTHROPIC_VERSION),
+        )];
+        headers.extend(self.auth.header());
+        apply_request_headers(&mut headers, &self.betas, &self.extra_headers);
+        headers
+    }
+
+    /// Creates a request with the default headers to the given path.
+    ///
+    /// The path is relative to the base URL, i.e. it should start with `/v1/`.
+    pub(crate) fn create_request<P: AsRef<str>, B: Into<Body>>(
+        &self,
+        method: &'static str,
+        path: P,
+        body: B,
+    ) -> HttpRequest {
+        HttpRequest {
+            scheme: self.base_url.scheme().to_string(),
+            host: self.base_url.host().to_string(),
+            path: format!("{}{}", self.base_url.path_prefix(), path.as_ref()),
+            query: Vec::new(),
+            method: method.into(),
+            headers: self.create_default_headers(),
+            secret_headers: self.secret_headers.clone(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Merges betas and extra headers into a set of headers.
+///
+/// All betas end up in a single, comma-separated `anthropic-beta` header without duplicates. Extra
+/// headers replace existing headers of the same name.
+pub(crate) fn apply_request_headers(
+    headers: &mut Vec<(Cow<'static, str>, Arc<str>)>,
+    betas: &[anthropic::Beta],
+    extra_headers: &[(Cow<'static, str>, Arc<str>)],
+) {
+    if !betas.is_empty() {
+        let existing = headers
+            .iter()
+            .position(|(key, _)| key.eq_ignore_ascii_case("anthropic-beta"));
+
+        let mut flags: Vec<&str> = existing
+            .map(|index| headers[index].1.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        for beta in betas {
+            if !flags.contains(&beta.as_str()) {
+                flags.push(beta.as_str());
+            }
+        }
+        let value = Arc::from(flags.join(","));
+
+        match existing {
+            Some(index) => headers[index].1 = value,
+            None => headers.push(("anthropic-beta".into(), value)),
+        }
+    }
+
+    for (name, value) in extra_headers {
+        match headers
+            .iter_mut()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        {
+            Some(header) => header.1 = value.clone(),
+            None => headers.push((name.clone(), value.clone())),
+        }
+    }
+}
+
+/// Collects query parameters, skipping unset ones.
+pub(crate) fn query_params<const N: usize>(
+    params: [(&str, Option<String>); N],
+) -> Vec<(String, String)> {
+    params
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value?)))
+        .collect()
+}
+
+/// A resource that can be listed using a [`ListRequestBuilder`].
+pub trait Listable {
+    /// Creates a request for the list endpoint of the resource, without pagination parameters.
+    fn list_request(api: &Api) -> HttpRequest;
+}
+
+/// Builder for a request listing resources of type `T`, e.g. models or message batches.
+///
+/// Results are paginated by resource ID. Once sent, you should expect to receive a
+/// [`Page<T>`](anthropic::Page) from the API.
+pub struct ListRequestBuilder<T> {
+    before_id: Option<String>,
+    after_id: Option<String>,
+    limit: Option<u32>,
+    _resource: PhantomData<fn() -> T>,
+}
+
+impl<T> ListRequestBuilder<T> {
+    /// Creates a new list request builder.
+    pub fn new() -> Self {
+        Self {
+            before_id: None,
+            after_id: None,
+            limit: None,
+            _resource: PhantomData,
+        }
+    }
+
+    /// Only return resources immediately before the given ID.
+    pub fn before_id<S: Into<String>>(mut self, before_id: S) -> Self {
+        self.before_id = Some(before_id.into());
+        self
+    }
+
+    /// Only return resources immediately after the given ID.
+    pub fn after_id<S: Into<String>>(mut self, after_id: S) -> Self {
+        self.after_id = Some(after_id.into());
+        self
+    }
+
+    /// Sets the number of resources to return per page (1 to 1000, defaults to 20).
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl<T: Listable> ListRequestBuilder<T> {
+    /// Builds an HTTP request for listing resources.
+    pub fn build(&self, api: &Api) -> HttpRequest {
+        let mut request = T::list_request(api);
+        request.query = query_params([
+            ("before_id", self.before_id.clone()),
+            ("after_id", self.after_id.clone()),
+            ("limit", self.limit.map(|limit| limit.to_string())),
+        ]);
+        request
+    }
+}
+
+impl<T> Clone for ListRequestBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            before_id: self.before_id.clone(),
+            after_id: self.after_id.clone(),
+            limit: self.limit,
+            _resource: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for ListRequestBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ListRequestBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListRequestBuilder")
+            .field("before_id", &self.before_id)
+            .field("after_id", &self.after_id)
+            .field("limit", &self.limit)
+            .finish()
     }
 }
 
@@ -88,7 +518,7 @@ impl Api {
 /// This builder is used to construct [`HttpRequest`]s for the `messages` endpoint. Once sent,
 /// you should expect to receive a [`crate::anthropic::MessagesResponse`] from the API, see
 /// [`crate::anthropic::deserialize_response`] for details.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MessagesRequestBuilder {
     /// The model to use for the request.
     ///
@@ -99,16 +529,36 @@ pub struct MessagesRequestBuilder {
     /// If none is provided, the default max tokens will be used.
     max_tokens: Option<u32>,
     /// The system prompt for the conversation.
-    system: Option<Arc<str>>,
+    system: Option<anthropic::SystemPrompt>,
     /// The messages to send.
     messages: im::Vector<anthropic::Message>,
     /// Tools available for the model to use.
     tools: Option<im::Vector<anthropic::Tool>>,
+    /// How the model should use the tools.
+    tool_choice: Option<anthropic::ToolChoice>,
+    /// Extended thinking configuration.
+    thinking: Option<anthropic::ThinkingConfig>,
+    /// Sampling temperature.
+    ///
+    /// If none is provided, the default from [`Api`] will be used, if any.
+    temperature: Option<f32>,
+    /// Top K sampling cut-off.
+    top_k: Option<u32>,
+    /// Nucleus sampling cut-off.
+    top_p: Option<f32>,
+    /// Custom sequences that stop generation.
+    stop_sequences: Option<Vec<String>>,
+    /// Request metadata.
+    metadata: Option<anthropic::Metadata>,
+    /// Service tier to use.
+    service_tier: Option<anthropic::ServiceTier>,
     /// Whether to stream the response.
     stream: bool,
-    // Note: Missing: container, mcp_servers, metadata, service_tier,
-    //                stop_sequences, temperature, thinking,
-    //                tool_choice, top_k, top_p
+    /// Beta features enabled for this request, in addition to those of the [`Api`].
+    betas: Vec<anthropic::Beta>,
+    /// Additional headers sent with this request.
+    extra_headers: Vec<(Cow<'static, str>, Arc<str>)>,
+    // Note: Missing: container, mcp_servers
 }
 
 impl Default for MessagesRequestBuilder {
@@ -126,7 +576,17 @@ impl MessagesRequestBuilder {
             system: None,
             messages: im::Vector::new(),
             tools: None,
+            tool_choice: None,
+            thinking: None,
+            temperature: None,
+            top_k: None,
+            top_p: None,
+            stop_sequences: None,
+            metadata: None,
+            service_tier: None,
             stream: false,
+            betas: Vec::new(),
+            extra_headers: Vec::new(),
         }
     }
 
@@ -152,8 +612,11 @@ impl MessagesRequestBuilder {
     /// conversation. See [Anthropic's documentation](https://docs.anthropic.com/en/api/system-prompts) for
     /// more details.
     ///
+    /// Besides strings, a [`anthropic::SystemPrompt`] made up of blocks can be passed, e.g. to set
+    /// cache breakpoints.
+    ///
     /// If not set, no system prompt is included in the request.
-    pub fn system<S: Into<Arc<str>>>(mut self, system: S) -> Self {
+    pub fn system<S: Into<anthropic::SystemPrompt>>(mut self, system: S) -> Self {
         self.system = Some(system.into());
         self
     }
@@ -179,63 +642,185 @@ impl MessagesRequestBuilder {
     }
 
     /// Sets the tools available for the model to use.
+    ///
+    /// To cache the tool definitions, set a cache breakpoint on the last tool, see
+    /// [`anthropic::Tool::with_cache_control`].
     pub fn set_tools<T: Into<im::Vector<anthropic::Tool>>>(mut self, tools: T) -> Self {
         self.tools = Some(tools.into());
         self
     }
 
+    /// Sets how the model should use the tools.
+    ///
+    /// If not set, the model decides on its own (see [`anthropic::ToolChoice::Auto`]).
+    pub fn tool_choice(mut self, tool_choice: anthropic::ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    /// Sets the extended thinking configuration.
+    ///
+    /// If not set, the API default (thinking disabled) is used. Note that the thinking budget
+    /// counts towards `max_tokens`, which must be larger than the budget.
+    pub fn thinking(mut self, thinking: anthropic::ThinkingConfig) -> Self {
+        self.thinking = Some(thinking);
+        self
+    }
+
+    /// Sets the sampling temperature.
+    ///
+    /// Ranges from `0.0` to `1.0`, use values closer to `0.0` for analytical tasks and closer to
+    /// `1.0` for creative ones. If not set, uses the default set by [`Api`], if any.
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets the top K sampling cut-off.
+    ///
+    /// Only recommended for advanced use cases, usually adjusting the temperature suffices.
+    pub fn top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Sets the nucleus sampling cut-off.
+    ///
+    /// Only recommended for advanced use cases, usually adjusting the temperature suffices.
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Sets custom stop sequences.
+    ///
+    /// If the model generates one of these, it stops with
+    /// [`anthropic::StopReason::StopSequence`].
+    pub fn stop_sequences<I, S>(mut self, stop_sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stop_sequences = Some(stop_sequences.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the request metadata.
+    pub fn metadata(mut self, metadata: anthropic::Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets the service tier.
+    pub fn service_tier(mut self, service_tier: anthropic::ServiceTier) -> Self {
+        self.service_tier = Some(service_tier);
+        self
+    }
+
     /// Sets whether to stream the response.
     pub fn stream(mut self, stream: bool) -> Self {
         self.stream = stream;
         self
     }
 
+    /// Enables a beta feature for this request.
+    ///
+    /// Merged with the betas enabled on the [`Api`], see [`Api::beta`].
+    pub fn beta<B: Into<anthropic::Beta>>(mut self, beta: B) -> Self {
+        self.betas.push(beta.into());
+        self
+    }
+
+    /// Adds a header to this request.
+    ///
+    /// Replaces headers of the same name, including those set through [`Api::extra_header`].
+    pub fn extra_header<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<Arc<str>>,
+    {
+        self.extra_headers.push((name.into(), value.into()));
+        self
+    }
+
     /// Builds the HTTP request.
     ///
     /// The resulting [`HttpRequest`] can be sent to the API using a suitable HTTP client.
     pub fn build(&self, api: &Api) -> HttpRequest {
-        let mut headers = api.create_default_headers();
+        let body = serde_json::to_string(&self.body(api)).expect("failed to serialize messages");
+        let mut request = api.create_request("POST", "/v1/messages", Body::Json(body));
+        let headers = &mut request.headers;
 
-        if let Some(model) = &self.model {
-            headers.push(("anthropic-model", Arc::from(model.as_str())));
-        } else {
-            headers.push(("anthropic-model", api.default_model.clone()));
-        }
+        headers.push((
+            "anthropic-model".into(),
+            Arc::from(self.resolved_model(api)),
+        ));
 
         if let Some(max_tokens) = self.max_tokens {
-            headers.push(("max-tokens", Arc::from(max_tokens.to_string())));
+            headers.push(("max-tokens".into(), Arc::from(max_tokens.to_string())));
         } else {
-            headers.push(("max-tokens", Arc::from(api.default_max_tokens.to_string())));
+            headers.push((
+                "max-tokens".into(),
+                Arc::from(api.default_max_tokens.to_string()),
+            ));
         }
 
-        let body = {
-            let model = if let Some(ref model) = self.model {
-                model.as_str()
-            } else {
-                &api.default_model
-            };
+        apply_request_headers(headers, &self.betas, &self.extra_headers);
 
-            let system = self.system.as_deref();
+        request
+    }
 
-            let body = anthropic::MessagesBody {
-                model,
-                max_tokens: self.max_tokens.unwrap_or(api.default_max_tokens),
-                system,
+    /// Returns the model to use, falling back to the default of `api`.
+    fn resolved_model<'a>(&'a self, api: &'a Api) -> &'a str {
+        self.model.as_deref().unwrap_or(&api.default_model)
+    }
+
+    /// Constructs the request body, filling in defaults from `api`.
+    pub(crate) fn body<'a>(&'a self, api: &'a Api) -> anthropic::MessagesBody<'a> {
+        anthropic::MessagesBody {
+            model: self.resolved_model(api),
+            max_tokens: self.max_tokens.unwrap_or(api.default_max_tokens),
+            system: self.system.as_ref(),
+            messages: &self.messages,
+            tools: self.tools.as_ref(),
+            tool_choice: self.tool_choice.as_ref(),
+            thinking: self.thinking,
+            temperature: self.temperature.or(api.default_temperature),
+            top_k: self.top_k.or(api.default_top_k),
+            top_p: self.top_p.or(api.default_top_p),
+            stop_sequences: self
+                .stop_sequences
+                .as_deref()
+                .or(api.default_stop_sequences.as_deref()),
+            metadata: self.metadata.as_ref().or(api.default_metadata.as_deref()),
+            service_tier: self.service_tier.or(api.default_service_tier),
+            stream: self.stream,
+        }
+    }
+
+    /// Builds an HTTP request for the token counting endpoint.
+    ///
+    /// Counts the input tokens the request would consume, without sending it to the model. Only
+    /// the model, system prompt, messages, tools, tool choice and thinking configuration are
+    /// taken into account. Once sent, you should expect to receive a
+    /// [`anthropic::TokenCount`] from the API.
+    pub fn build_count_tokens(&self, api: &Api) -> HttpRequest {
+        let body = {
+            let body = anthropic::CountTokensBody {
+                model: self.resolved_model(api),
+                system: self.system.as_ref(),
                 messages: &self.messages,
                 tools: self.tools.as_ref(),
-                stream: self.stream,
+                tool_choice: self.tool_choice.as_ref(),
+                thinking: self.thinking,
             };
 
-            serde_json::to_string(&body).expect("failed to serialize messages")
+            serde_json::to_string(&body).expect("failed to serialize token count request")
         };
 
-        HttpRequest {
-            host: api.endpoint_host.to_string(),
-            path: "/v1/messages".to_string(),
-            method: "POST",
-            headers,
-            body,
-        }
+        let mut request = api.create_request("POST", "/v1/messages/count_tokens", Body::Json(body));
+        apply_request_headers(&mut request.headers, &self.betas, &self.extra_headers);
+        request
     }
 }
 
@@ -254,6 +839,45 @@ pub enum ResponseError {
         expected: &'static str,
         actual: &'static str,
     },
+    /// The API returned a non-success status without a recognizable error.
+    ///
+    /// Usually caused by proxies or load balancers in front of the API.
+    #[error("Unexpected HTTP status {status}")]
+    HttpStatus {
+        status: u16,
+        request_id: Option<String>,
+        body: String,
+    },
+    /// The API returned a success status, but the body is not JSON.
+    #[error("Response body is not JSON (content type {content_type:?})")]
+    NotJson {
+        status: u16,
+        content_type: Option<String>,
+        body: String,
+    },
+}
+
+/// Error converting an [`ApiResponse`] into a specific response type.
+///
+/// Conversions returning `()` as their error are treated as [`ConversionError::UnexpectedType`].
+#[derive(Debug)]
+pub enum ConversionError {
+    /// The response is of a different type.
+    UnexpectedType,
+    /// The response has the right type, but its contents could not be deserialized.
+    Deserialize(serde_json::Error),
+}
+
+impl From<()> for ConversionError {
+    fn from((): ()) -> Self {
+        ConversionError::UnexpectedType
+    }
+}
+
+impl From<serde_json::Error> for ConversionError {
+    fn from(err: serde_json::Error) -> Self {
+        ConversionError::Deserialize(err)
+    }
 }
 
 /// Deserializes an Anthropic API response from JSON.
@@ -261,20 +885,89 @@ pub enum ResponseError {
 /// This is the central low-level entry point for parsing responses from the API.
 pub fn deserialize_response<T>(json: &str) -> Result<T, ResponseError>
 where
-    T: TryFrom<ApiResponse, Error = ()>,
+    T: TryFrom<ApiResponse, Error: Into<ConversionError>>,
 {
     let api_response: ApiResponse = serde_json::from_str(json)?;
+    convert_response(api_response, None)
+}
 
+/// Parses an HTTP response from the Anthropic API.
+///
+/// Unlike [`deserialize_response`], this takes the status code and headers into account:
+///
+/// * API errors are returned as [`ResponseError::Api`], with the request ID taken from the
+///   `request-id` header if the body does not contain it.
+/// * Other non-success responses, e.g. HTML error pages of proxies, result in
+///   [`ResponseError::HttpStatus`].
+/// * Successful responses that are not JSON result in [`ResponseError::NotJson`].
+///
+/// # Example
+///
+/// ```
+/// # use claus::{ResponseError, anthropic::MessagesResponse, http_response::HttpResponse, parse_response};
+/// let response = HttpResponse::new(502, Vec::new(), "<html>Bad Gateway</html>");
+/// let result: Result<MessagesResponse, _> = parse_response(&response);
+/// assert!(matches!(result, Err(ResponseError::HttpStatus { status: 502, .. })));
+/// ```
+pub fn parse_response<T>(response: &http_response::HttpResponse) -> Result<T, ResponseError>
+where
+    T: TryFrom<ApiResponse, Error: Into<ConversionError>>,
+{
+    let body = response.body_text();
+
+    match serde_json::from_str::<ApiResponse>(&body) {
+        Ok(api_response @ ApiResponse::Error { .. }) => {
+            convert_response(api_response, response.request_id())
+        }
+        Ok(api_response) if response.is_success() => convert_response(api_response, None),
+        Err(err) if response.is_success() => {
+            if serde_json::from_str::<serde::de::IgnoredAny>(&body).is_ok() {
+                Err(err.into())
+            } else {
+                Err(ResponseError::NotJson {
+                    status: response.status,
+                    content_type: response.header("content-type").map(ToOwned::to_owned),
+                    body: body.into_owned(),
+                })
+            }
+        }
+        _ => Err(ResponseError::HttpStatus {
+            status: response.status,
+            request_id: response.request_id().map(ToOwned::to_owned),
+            body: body.into_owned(),
+        }),
+    }
+}
+
+/// Converts a deserialized response into the expected type.
+///
+/// The `fallback_request_id` is used for errors if the response itself contains none.
+fn convert_response<T>(
+    api_response: ApiResponse,
+    fallback_request_id: Option<&str>,
+) -> Result<T, ResponseError>
+where
+    T: TryFrom<ApiResponse, Error: Into<ConversionError>>,
+{
     match api_response {
-        ApiResponse::Error { error } => Err(ResponseError::Api(error)),
+        ApiResponse::Error {
+            mut error,
+            request_id,
+        } => {
+            if let Some(request_id) = request_id.or(fallback_request_id.map(ToOwned::to_owned)) {
+                error.set_request_id(Some(request_id));
+            }
+            Err(ResponseError::Api(error))
+        }
         other => {
             let kind = other.kind();
-            other
-                .try_into()
-                .map_err(|()| ResponseError::UnexpectedResponseType {
+            other.try_into().map_err(|err: T::Error| match err.into() {
+                ConversionError::UnexpectedType => ResponseError::UnexpectedResponseType {
                     expected: std::any::type_name::<T>(),
                     actual: kind,
-                })
+                },
+                ConversionError::Deserialize(err) => ResponseError::Serde(err),
+            })
         }
     }
 }
@@ -334,14 +1027,24 @@ mod tests {
   "error": {
     "type": "not_found_error",
     "message": "The requested resource could not be found."
-  }
+  },
+  "request_id": "req_011CSHoEeqs5C35K2UUqR7Fy"
 }"#;
 
         let result: Result<MessagesResponse, _> = deserialize_response(json);
 
         assert!(result.is_err());
         if let Err(super::ResponseError::Api(api_error)) = result {
-            assert!(matches!(api_error, ApiError::NotFoundError));
+            assert!(matches!(api_error, ApiError::NotFoundError { .. }));
+            assert_eq!(
+                api_error.message(),
+                "The requested resource could not be found."
+            );
+            assert_eq!(api_error.request_id(), Some("req_011CSHoEeqs5C35K2UUqR7Fy"));
+            assert_eq!(
+                api_error.to_string(),
+                "Not found: The requested resource could not be found."
+            );
         } else {
             panic!("Expected Api error");
         }
@@ -361,12 +1064,90 @@ mod tests {
 
         assert!(result.is_err());
         if let Err(super::ResponseError::Api(api_error)) = result {
-            assert!(matches!(api_error, ApiError::InvalidRequestError));
+            assert!(matches!(api_error, ApiError::InvalidRequestError { .. }));
+            assert_eq!(api_error.request_id(), None);
         } else {
             panic!("Expected Api error");
         }
     }
 
+    #[test]
+    fn test_parse_http_response() {
+        use super::{ResponseError, http_response::HttpResponse, parse_response};
+
+        let headers = vec![("request-id".to_string(), "req_01".to_string())];
+
+        let response = HttpResponse::new(
+            429,
+            headers.clone(),
+            r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Slow down"}}"#,
+        );
+        let result: Result<MessagesResponse, _> = parse_response(&response);
+        let Err(ResponseError::Api(api_error)) = result else {
+            panic!("Expected Api error, got {:?}", result);
+        };
+        assert!(matches!(api_error, ApiError::RateLimitError { .. }));
+        assert_eq!(api_error.request_id(), Some("req_01"));
+
+        let response = HttpResponse::new(503, headers.clone(), "<html>Unavailable</html>");
+        let result: Result<MessagesResponse, _> = parse_response(&response);
+        assert!(matches!(
+            result,
+            Err(ResponseError::HttpStatus { status: 503, request_id: Some(ref id), ref body })
+                if id == "req_01" && body == "<html>Unavailable</html>"
+        ));
+
+        let response = HttpResponse::new(
+            200,
+            vec![("content-type".to_string(), "text/html".to_string())],
+            "<html>Welcome to the captive portal</html>",
+        );
+        let result: Result<MessagesResponse, _> = parse_response(&response);
+        assert!(matches!(
+            result,
+            Err(ResponseError::NotJson { status: 200, content_type: Some(ref content_type), .. })
+                if content_type == "text/html"
+        ));
+
+        let response = HttpResponse::new(200, headers, r#"{"input_tokens": 12}"#);
+        let count: super::anthropic::TokenCount = parse_response(&response).unwrap();
+        assert_eq!(count.input_tokens, 12);
+
+        let result: Result<MessagesResponse, _> = parse_response(&response);
+        assert!(matches!(
+            result,
+            Err(ResponseError::UnexpectedResponseType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_api_error_and_stream_error() {
+        let json =
+            r#"{"type": "error", "error": {"type": "teapot_error", "message": "I'm a teapot"}}"#;
+
+        let result: Result<MessagesResponse, _> = deserialize_response(json);
+        let Err(super::ResponseError::Api(api_error)) = result else {
+            panic!("Expected Api error, got {:?}", result);
+        };
+        assert_eq!(api_error.error_type(), "teapot_error");
+        assert_eq!(api_error.message(), "I'm a teapot");
+        assert_eq!(
+            serde_json::to_value(&api_error).unwrap(),
+            serde_json::json!({"type": "teapot_error", "message": "I'm a teapot"})
+        );
+
+        let event = super::deserialize_event(
+            br#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        )
+        .unwrap();
+        let super::anthropic::StreamEvent::Error { error } = event else {
+            panic!("Expected error event, got {:?}", event);
+        };
+        assert!(
+            matches!(error, ApiError::OverloadedError { ref message, .. } if message == "Overloaded")
+        );
+    }
+
     #[test]
     fn test_api_response_message_deserialization() {
         let json = r#"{
@@ -400,7 +1181,7 @@ mod tests {
         assert_eq!(response.usage.output_tokens, 503);
         assert_eq!(response.message.content.len(), 1);
 
-        let Content::Text { text } = &response.message.content[0] else {
+        let Content::Text { text, .. } = &response.message.content[0] else {
             panic!("should be text");
         };
         assert_eq!(text, "Hi! My name is Claude.");
@@ -423,11 +1204,31 @@ mod tests {
         assert!(
             http_request
                 .body
+                .as_json()
+                .unwrap()
                 .contains("\"system\":\"You are a helpful assistant.\"")
         );
-        assert!(http_request.body.contains("\"messages\":["));
-        assert!(http_request.body.contains("\"role\":\"user\""));
-        assert!(http_request.body.contains("\"text\":\"Hello!\""));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"messages\":[")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"role\":\"user\"")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"text\":\"Hello!\"")
+        );
     }
 
     #[test]
@@ -466,26 +1267,349 @@ mod tests {
         assert_eq!(http_request.host, "api.anthropic.com");
 
         // Verify the body contains the tools
-        assert!(http_request.body.contains("\"tools\":["));
-        assert!(http_request.body.contains("\"name\":\"get_weather\""));
+        assert!(http_request.body.as_json().unwrap().contains("\"tools\":["));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"name\":\"get_weather\"")
+        );
         assert!(
             http_request
                 .body
+                .as_json()
+                .unwrap()
                 .contains("\"description\":\"Get the current weather in a given location\"")
         );
-        assert!(http_request.body.contains("\"input_schema\""));
-        assert!(http_request.body.contains("\"properties\""));
-        assert!(http_request.body.contains("\"location\""));
-        assert!(http_request.body.contains("\"unit\""));
-        assert!(http_request.body.contains("\"required\":[\"location\"]"));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"input_schema\"")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"properties\"")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"location\"")
+        );
+        assert!(http_request.body.as_json().unwrap().contains("\"unit\""));
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"required\":[\"location\"]")
+        );
 
         // Verify the message is also present
-        assert!(http_request.body.contains("\"messages\":["));
-        assert!(http_request.body.contains("\"role\":\"user\""));
         assert!(
             http_request
                 .body
+                .as_json()
+                .unwrap()
+                .contains("\"messages\":[")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains("\"role\":\"user\"")
+        );
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
                 .contains("\"What's the weather in San Francisco?\"")
         );
     }
+
+    #[test]
+    fn test_messages_request_builder_with_thinking() {
+        let api = super::Api::new("test-api-key");
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .max_tokens(4096)
+            .thinking(super::anthropic::ThinkingConfig::enabled(2048))
+            .push_message(super::anthropic::Role::User, "Think hard.")
+            .build(&api);
+
+        assert!(
+            http_request
+                .body
+                .as_json()
+                .unwrap()
+                .contains(r#""thinking":{"type":"enabled","budget_tokens":2048}"#)
+        );
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .push_message(super::anthropic::Role::User, "Don't think.")
+            .build(&api);
+        assert!(!http_request.body.as_json().unwrap().contains("thinking"));
+    }
+
+    #[test]
+    fn test_messages_request_builder_sampling_parameters() {
+        use super::anthropic::{Metadata, ServiceTier};
+
+        let api = super::Api::new("test-api-key")
+            .default_temperature(0.5)
+            .default_stop_sequences(["STOP"])
+            .default_service_tier(ServiceTier::StandardOnly);
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .push_message(super::anthropic::Role::User, "Hello!")
+            .build(&api);
+        let body: serde_json::Value =
+            serde_json::from_str(http_request.body.as_json().unwrap()).unwrap();
+        assert_eq!(body["temperature"], 0.5);
+        assert_eq!(body["stop_sequences"], serde_json::json!(["STOP"]));
+        assert_eq!(body["service_tier"], "standard_only");
+        assert!(body.get("top_k").is_none());
+        assert!(body.get("top_p").is_none());
+        assert!(body.get("metadata").is_none());
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .push_message(super::anthropic::Role::User, "Hello!")
+            .temperature(0.0)
+            .top_k(5)
+            .top_p(0.25)
+            .stop_sequences(["\n\nHuman:", "END"])
+            .metadata(Metadata::user_id("user-1234"))
+            .service_tier(ServiceTier::Auto)
+            .build(&api);
+        let body: serde_json::Value =
+            serde_json::from_str(http_request.body.as_json().unwrap()).unwrap();
+        assert_eq!(body["temperature"], 0.0);
+        assert_eq!(body["top_k"], 5);
+        assert_eq!(body["top_p"], 0.25);
+        assert_eq!(
+            body["stop_sequences"],
+            serde_json::json!(["\n\nHuman:", "END"])
+        );
+        assert_eq!(body["metadata"]["user_id"], "user-1234");
+        assert_eq!(body["service_tier"], "auto");
+    }
+
+    #[test]
+    fn test_messages_request_builder_with_tool_choice() {
+        use super::anthropic::ToolChoice;
+
+        let api = super::Api::new("test-api-key");
+
+        let build = |tool_choice: ToolChoice| {
+            let http_request = super::MessagesRequestBuilder::new()
+                .push_message(super::anthropic::Role::User, "Extract the data.")
+                .tool_choice(tool_choice)
+                .build(&api);
+            let body: serde_json::Value =
+                serde_json::from_str(http_request.body.as_json().unwrap()).unwrap();
+            body["tool_choice"].clone()
+        };
+
+        assert_eq!(
+            build(ToolChoice::auto()),
+            serde_json::json!({"type": "auto"})
+        );
+        assert_eq!(
+            build(ToolChoice::any().disable_parallel_tool_use()),
+            serde_json::json!({"type": "any", "disable_parallel_tool_use": true})
+        );
+        assert_eq!(
+            build(ToolChoice::tool("extract")),
+            serde_json::json!({"type": "tool", "name": "extract"})
+        );
+        assert_eq!(
+            build(ToolChoice::none()),
+            serde_json::json!({"type": "none"})
+        );
+    }
+
+    #[test]
+    fn test_messages_request_builder_with_cached_system_prompt() {
+        use super::anthropic::{CacheControl, SystemPrompt};
+
+        let api = super::Api::new("test-api-key");
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .system(
+                SystemPrompt::from("You are a helpful assistant.")
+                    .with_cache_control(CacheControl::ephemeral()),
+            )
+            .push_message(super::anthropic::Role::User, "Hello!")
+            .build(&api);
+
+        assert!(http_request.body.as_json().unwrap().contains(
+            r#""system":[{"type":"text","text":"You are a helpful assistant.","cache_control":{"type":"ephemeral"}}]"#
+        ));
+    }
+
+    #[test]
+    fn test_beta_and_extra_headers() {
+        let api = super::Api::new("test-api-key")
+            .beta(super::anthropic::Beta::FilesApi)
+            .beta("files-api-2025-04-14")
+            .extra_header("x-custom", "api")
+            .extra_header(String::from("x-from-config"), "config");
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .beta(super::anthropic::Beta::InterleavedThinking)
+            .beta("some-new-beta-2099-01-01")
+            .extra_header("x-custom", "request")
+            .push_message(super::anthropic::Role::User, "Hello!")
+            .build(&api);
+
+        let beta_headers: Vec<_> = http_request
+            .headers
+            .iter()
+            .filter(|(key, _)| *key == "anthropic-beta")
+            .collect();
+        assert_eq!(beta_headers.len(), 1);
+        assert_eq!(
+            beta_headers[0].1.as_ref(),
+            "files-api-2025-04-14,interleaved-thinking-2025-05-14,some-new-beta-2099-01-01"
+        );
+
+        let custom: Vec<_> = http_request
+            .headers
+            .iter()
+            .filter(|(key, _)| *key == "x-custom")
+            .map(|(_, value)| value.as_ref())
+            .collect();
+        assert_eq!(custom, ["request"]);
+        assert!(
+            http_request
+                .render_headers()
+                .contains("x-from-config: config")
+        );
+
+        // Betas are merged into an `anthropic-beta` header set through extra headers, too.
+        let http_request = super::MessagesRequestBuilder::new()
+            .beta(super::anthropic::Beta::InterleavedThinking)
+            .build(&super::Api::new("test-api-key").extra_header("Anthropic-Beta", "custom-beta"));
+        let beta_headers: Vec<_> = http_request
+            .headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("anthropic-beta"))
+            .map(|(_, value)| value.as_ref())
+            .collect();
+        assert_eq!(
+            beta_headers,
+            ["custom-beta,interleaved-thinking-2025-05-14"]
+        );
+
+        // Files requests merge their beta with the ones from the `Api`.
+        let http_request = crate::files::retrieve(&api, "file_01");
+        assert!(
+            http_request
+                .render_headers()
+                .contains("anthropic-beta: files-api-2025-04-14\n")
+        );
+    }
+
+    #[test]
+    fn test_auth_strategies() {
+        use super::{Api, Auth, MessagesRequestBuilder};
+
+        let http_request = MessagesRequestBuilder::new()
+            .build(&Api::with_auth(Auth::BearerToken("oauth-token".into())));
+        assert!(
+            http_request
+                .render_headers()
+                .contains("authorization: <redacted>\n")
+        );
+        assert!(
+            http_request
+                .render_unredacted()
+                .contains("authorization: Bearer oauth-token\n")
+        );
+        assert!(!http_request.render_unredacted().contains("x-api-key"));
+
+        let api = Api::new("sk-ant-unused").auth(Auth::Header {
+            name: String::from("x-proxy-auth").into(),
+            value: "proxy-secret".into(),
+        });
+        assert!(!format!("{api:?}").contains("proxy-secret"));
+        let http_request = MessagesRequestBuilder::new().build(&api);
+        assert!(
+            http_request
+                .to_string()
+                .contains("x-proxy-auth: <redacted>\n")
+        );
+        assert!(!http_request.render_unredacted().contains("sk-ant-unused"));
+
+        let http_request = MessagesRequestBuilder::new().build(&Api::with_auth(Auth::None));
+        assert_eq!(
+            http_request
+                .headers
+                .iter()
+                .map(|(key, _)| key.as_ref())
+                .collect::<Vec<_>>(),
+            ["anthropic-version", "anthropic-model", "max-tokens"]
+        );
+    }
+
+    #[test]
+    fn test_secret_redaction() {
+        let api = super::Api::new("sk-ant-secret")
+            .extra_header("x-gateway-token", "gw-secret")
+            .secret_header(String::from("X-Gateway-Token"));
+        let debug = format!("{api:?}");
+        assert!(!debug.contains("sk-ant-secret"));
+        assert!(!debug.contains("gw-secret"));
+        assert!(debug.contains("x-gateway-token"));
+
+        let http_request = super::MessagesRequestBuilder::new().build(&api);
+        let rendered = http_request.to_string();
+        assert!(rendered.contains("x-api-key: <redacted>\n"));
+        assert!(rendered.contains("x-gateway-token: <redacted>\n"));
+
+        let unredacted = http_request.render_unredacted();
+        assert!(unredacted.contains("x-api-key: sk-ant-secret\n"));
+        assert!(unredacted.contains("x-gateway-token: gw-secret\n"));
+    }
+
+    #[test]
+    fn test_count_tokens_request_and_response() {
+        let api = super::Api::new("test-api-key");
+
+        let http_request = super::MessagesRequestBuilder::new()
+            .system("You are a helpful assistant.")
+            .max_tokens(2048)
+            .temperature(0.5)
+            .push_message(super::anthropic::Role::User, "Hello!")
+            .build_count_tokens(&api);
+
+        assert_eq!(http_request.method, "POST");
+        assert_eq!(http_request.path, "/v1/messages/count_tokens");
+        assert_eq!(
+            http_request.body.as_json().unwrap(),
+            r#"{"model":"claude-sonnet-4-20250514","system":"You are a helpful assistant.","messages":[{"role":"user","content":[{"type":"text","text":"Hello!"}]}]}"#
+        );
+
+        let count: super::anthropic::TokenCount =
+            deserialize_response(r#"{"input_tokens": 2095}"#).expect("should deserialize");
+        assert_eq!(count.input_tokens, 2095);
+
+        let result: Result<MessagesResponse, _> = deserialize_response(r#"{"input_tokens": 1}"#);
+        assert!(matches!(
+            result,
+            Err(super::ResponseError::UnexpectedResponseType {
+                actual: "token_count",
+                ..
+            })
+        ));
+    }
 }
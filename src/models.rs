@@ -0,0 +1,119 @@
+//! Models API.
+//!
+//! Allows discovering the models available to an API key. See
+//! <https://docs.anthropic.com/en/api/models-list> for details.
+//!
+//! ## Example
+//!
+//! ```
+//! use claus::{Api, anthropic::{ModelInfo, Page}, models::ListModelsRequestBuilder};
+//!
+//! let api = Api::new("sk-ant-api03-...");
+//!
+//! let http_req = ListModelsRequestBuilder::new().limit(5).build(&api);
+//! assert_eq!(http_req.path_and_query(), "/v1/models?limit=5");
+//!
+//! // Once sent, the response can be deserialized into a page of models.
+//! let json = r#"{
+//!     "data": [{
+//!         "type": "model",
+//!         "id": "claude-sonnet-4-20250514",
+//!         "display_name": "Claude Sonnet 4",
+//!         "created_at": "2025-05-22T00:00:00Z"
+//!     }],
+//!     "has_more": false,
+//!     "first_id": "claude-sonnet-4-20250514",
+//!     "last_id": "claude-sonnet-4-20250514"
+//! }"#;
+//! let page: Page<ModelInfo> = claus::deserialize_response(json).unwrap();
+//! assert_eq!(page.data[0].display_name, "Claude Sonnet 4");
+//! ```
+
+use crate::{
+    Api, ListRequestBuilder, Listable,
+    anthropic::ModelInfo,
+    http_request::{Body, HttpRequest, path_segment},
+};
+
+/// Path of the models endpoint.
+const MODELS_PATH: &str = "/v1/models";
+
+/// Builder for a request listing available models.
+///
+/// Models are returned most recently released first. Once sent, you should expect to receive a
+/// [`Page<ModelInfo>`](crate::anthropic::Page) from the API.
+pub type ListModelsRequestBuilder = ListRequestBuilder<ModelInfo>;
+
+impl Listable for ModelInfo {
+    fn list_request(api: &Api) -> HttpRequest {
+        api.create_request("GET", MODELS_PATH, Body::Empty)
+    }
+}
+
+/// Builds an HTTP request retrieving information about a model.
+///
+/// Model aliases such as `claude-sonnet-4-0` are resolved to the model they point to. Once sent,
+/// you should expect to receive a [`ModelInfo`](crate::anthropic::ModelInfo) from the API.
+///
+/// # Panics
+///
+/// Panics if `model_id` is empty, `.` or `..`.
+pub fn retrieve(api: &Api, model_id: &str) -> HttpRequest {
+    api.create_request(
+        "GET",
+        format!("{MODELS_PATH}/{}", path_segment(model_id)),
+        Body::Empty,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListModelsRequestBuilder;
+    use crate::{Api, anthropic::ModelInfo, deserialize_response};
+
+    #[test]
+    fn test_models_requests() {
+        let api = Api::new("test-key");
+
+        let http_req = ListModelsRequestBuilder::new()
+            .before_id("claude-opus-4-20250514")
+            .after_id("claude-3-haiku-20240307")
+            .limit(2)
+            .build(&api);
+        assert_eq!(http_req.method, "GET");
+        assert_eq!(
+            http_req.path_and_query(),
+            "/v1/models?before_id=claude-opus-4-20250514&after_id=claude-3-haiku-20240307&limit=2"
+        );
+        assert_eq!(
+            ListModelsRequestBuilder::new().build(&api).path_and_query(),
+            "/v1/models"
+        );
+
+        let http_req = super::retrieve(&api, "claude-sonnet-4-0");
+        assert_eq!(http_req.path, "/v1/models/claude-sonnet-4-0");
+
+        let http_req = super::retrieve(&api, "x/../../files");
+        assert_eq!(http_req.path, "/v1/models/x%2F..%2F..%2Ffiles");
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_empty_model_id() {
+        super::retrieve(&Api::new("test-key"), "");
+    }
+
+    #[test]
+    fn test_deserialize_model() {
+        let model: ModelInfo = deserialize_response(
+            r#"{
+                "type": "model",
+                "id": "claude-sonnet-4-20250514",
+                "display_name": "Claude Sonnet 4",
+                "created_at": "2025-05-22T00:00:00Z"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(model.id, "claude-sonnet-4-20250514");
+    }
+}
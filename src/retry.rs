@@ -0,0 +1,266 @@
+//! I/O-less retry policy.
+//!
+//! Requests to the API may fail temporarily, e.g. when hitting a rate limit (HTTP 429) or when the
+//! API is overloaded (HTTP 529). A [`RetryPolicy`] decides whether and when a failed request
+//! should be sent again, leaving the actual waiting and resending to the caller.
+//!
+//! ## Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use claus::{http_response::HttpResponse, retry::RetryPolicy};
+//!
+//! let policy = RetryPolicy::new().max_attempts(3);
+//!
+//! let response = HttpResponse::new(
+//!     429,
+//!     vec![("retry-after".to_string(), "2".to_string())],
+//!     r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Slow down"}}"#,
+//! );
+//!
+//! // After the first attempt, the API's request to wait two seconds is honored.
+//! assert_eq!(policy.retry_delay(1, &response), Some(Duration::from_secs(2)));
+//! // After the third attempt, we give up.
+//! assert_eq!(policy.retry_delay(3, &response), None);
+//! ```
+
+use std::{
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use crate::{anthropic::ApiError, http_response::HttpResponse};
+
+/// Policy deciding whether and when to retry failed requests.
+///
+/// Uses exponential backoff with jitter, unless the API sends a `retry-after` header.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    max_attempts: u32,
+    /// Delay before the first retry.
+    initial_backoff: Duration,
+    /// Upper bound for the exponential backoff.
+    max_backoff: Duration,
+    /// Fraction of the backoff that is randomized, between `0.0` and `1.0`.
+    jitter: f64,
+    /// Upper bound for delays requested by the API, no retry is made if a longer one is requested.
+    max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new retry policy with default settings.
+    ///
+    /// By default, requests are attempted up to 3 times, with a backoff starting at 500 ms and
+    /// growing up to 8 seconds, randomized by 25%.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            jitter: 0.25,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Creates a policy that never retries.
+    pub fn never() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry, doubled for every further retry.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the upper bound for the exponential backoff.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the fraction of the backoff that is randomized.
+    ///
+    /// A value of `0.25` results in delays between 75% and 100% of the backoff, `0.0` disables
+    /// jitter. Clamped to `0.0..=1.0`.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the upper bound for delays requested by the API through `retry-after`.
+    ///
+    /// If the API requests a longer delay, the request is not retried, since retrying earlier would
+    /// only be rejected again.
+    pub fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Decides whether to retry after receiving a response.
+    ///
+    /// `attempt` is the number of attempts made so far, i.e. `1` after the first request. Returns
+    /// the delay after which to resend the request, or `None` if the request should not be
+    /// retried. A delay requested through `retry-after` is honored, unless it exceeds
+    /// [`RetryPolicy::max_retry_after`], in which case `None` is returned.
+    pub fn retry_delay(&self, attempt: u32, response: &HttpResponse) -> Option<Duration> {
+        if attempt >= self.max_attempts || response.is_success() {
+            return None;
+        }
+
+        // The API may explicitly tell us whether to retry.
+        match response.header("x-should-retry") {
+            Some("true") => {}
+            Some("false") => return None,
+            _ => {
+                if !is_retryable_status(response.status) {
+                    return None;
+                }
+            }
+        }
+
+        match response.retry_after() {
+            Some(retry_after) if retry_after <= self.max_retry_after => Some(retry_after),
+            Some(_) => None,
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// Decides whether to retry after an API error that was not accompanied by a response.
+    ///
+    /// Useful for errors received while streaming, see
+    /// [`StreamEvent::Error`](crate::anthropic::StreamEvent::Error). See
+    /// [`RetryPolicy::retry_delay`] for the meaning of `attempt`.
+    pub fn retry_delay_for_error(&self, attempt: u32, error: &ApiError) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    /// Calculates the randomized backoff after the given attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        backoff.mul_f64(1.0 - self.jitter * random_fraction())
+    }
+}
+
+/// Returns whether a status code indicates a temporary failure.
+///
+/// Includes rate limiting (429), overloading (529) and other server errors.
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 409 | 429 | 500..=599)
+}
+
+/// Returns a random number in `0.0..1.0`.
+///
+/// Randomness is taken from the randomly seeded [`RandomState`], which suffices for jitter.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().hash_one(0u8);
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::{anthropic::ApiError, http_response::HttpResponse};
+
+    #[test]
+    fn test_retry_decisions() {
+        let policy = RetryPolicy::new()
+            .max_attempts(4)
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3))
+            .jitter(0.0);
+
+        let overloaded = HttpResponse::new(529, Vec::new(), "");
+        assert_eq!(
+            policy.retry_delay(1, &overloaded),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            policy.retry_delay(2, &overloaded),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.retry_delay(3, &overloaded),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(policy.retry_delay(4, &overloaded), None);
+
+        let bad_request = HttpResponse::new(400, Vec::new(), "");
+        assert_eq!(policy.retry_delay(1, &bad_request), None);
+
+        let ok = HttpResponse::new(200, Vec::new(), "");
+        assert_eq!(policy.retry_delay(1, &ok), None);
+
+        let should_not_retry = HttpResponse::new(
+            500,
+            vec![("x-should-retry".to_string(), "false".to_string())],
+            "",
+        );
+        assert_eq!(policy.retry_delay(1, &should_not_retry), None);
+
+        // Excessive delays requested by the API result in giving up.
+        let rate_limited = HttpResponse::new(
+            429,
+            vec![("retry-after".to_string(), "3600".to_string())],
+            "",
+        );
+        assert_eq!(policy.retry_delay(1, &rate_limited), None);
+        let rate_limited =
+            HttpResponse::new(429, vec![("retry-after".to_string(), "30".to_string())], "");
+        assert_eq!(
+            policy.retry_delay(1, &rate_limited),
+            Some(Duration::from_secs(30))
+        );
+
+        let overloaded = ApiError::OverloadedError {
+            message: "Overloaded".to_string(),
+            request_id: None,
+        };
+        assert_eq!(
+            policy.retry_delay_for_error(1, &overloaded),
+            Some(Duration::from_secs(1))
+        );
+        let invalid = ApiError::InvalidRequestError {
+            message: "Bad".to_string(),
+            request_id: None,
+        };
+        assert_eq!(policy.retry_delay_for_error(1, &invalid), None);
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_secs(4))
+            .jitter(0.5);
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(1);
+            assert!(backoff >= Duration::from_secs(2) && backoff <= Duration::from_secs(4));
+        }
+    }
+}
@@ -0,0 +1,638 @@
+//! Accumulation of streaming responses.
+//!
+//! When `stream` is set on a request, the API returns a series of [`StreamEvent`]s instead of a
+//! single [`MessagesResponse`]. The [`StreamAccumulator`] consumes these events and assembles the
+//! response piece by piece, yielding a [`MessagesResponse`] identical to the one the non-streaming
+//! API would have returned once the stream is complete.
+//!
+//! Like the rest of the crate, the accumulator performs no I/O; the caller is responsible for
+//! receiving the response body. The [`SseDecoder`] turns the raw bytes of the body into events,
+//! alternatively already split Server-Sent Events can be decoded using
+//! [`crate::deserialize_event`].
+//!
+//! ## Example
+//!
+//! ```
+//! use claus::{deserialize_event, streaming::StreamAccumulator};
+//!
+//! let events = [
+//!     r#"{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}"#,
+//!     r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+//!     r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello!"}}"#,
+//!     r#"{"type":"content_block_stop","index":0}"#,
+//!     r#"{"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":5}}"#,
+//!     r#"{"type":"message_stop"}"#,
+//! ];
+//!
+//! let mut accumulator = StreamAccumulator::new();
+//! for event in events {
+//!     let event = deserialize_event(event.as_bytes()).expect("valid event");
+//!     accumulator.push(event).expect("valid stream");
+//! }
+//!
+//! assert!(accumulator.is_complete());
+//! let response = accumulator.finish().expect("complete stream");
+//! assert_eq!(response.message.content[0].as_text(), Some("Hello!"));
+//! assert_eq!(response.usage.output_tokens, 5);
+//! ```
+
+pub mod partial_json;
+
+use serde_json::Value;
+
+use crate::anthropic::{
+    ApiError, Content, Delta, Message, MessagesResponse, StreamEvent, StreamingMessage,
+    StreamingUsage, ToolUse,
+};
+
+/// Push-based decoder for Server-Sent Events.
+///
+/// Streamed responses are delivered as a `text/event-stream`. The decoder is fed the raw body in
+/// chunks of arbitrary size as they arrive, and returns all events completed by each chunk. Lines
+/// may be terminated by LF, CRLF or CR, comments are skipped and multi-line `data` fields are
+/// joined. Data is decoded using [`crate::deserialize_event`], which determines the event type
+/// from the payload itself, so the `event` field is not used.
+///
+/// # Example
+///
+/// ```
+/// use claus::{anthropic::StreamEvent, streaming::SseDecoder};
+///
+/// let mut decoder = SseDecoder::new();
+///
+/// // Events may be split at any point.
+/// assert_eq!(decoder.feed(b"event: ping\r\ndata: {\"ty").count(), 0);
+///
+/// let events: Vec<_> = decoder.feed(b"pe\": \"ping\"}\r\n\r\n").collect();
+/// assert!(matches!(events[..], [Ok(StreamEvent::Ping)]));
+/// ```
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// The current, incomplete line.
+    line: Vec<u8>,
+    /// Data of the current event.
+    data: Vec<u8>,
+    /// Whether the previous chunk ended in a carriage return.
+    ///
+    /// A line feed immediately following it belongs to the same line terminator.
+    skip_lf: bool,
+    /// Whether the first line has been processed, used to strip a leading byte order mark.
+    started: bool,
+}
+
+impl SseDecoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a chunk of the response body into the decoder.
+    ///
+    /// Returns all events completed by this chunk. Incomplete events are kept until the next
+    /// call.
+    pub fn feed(
+        &mut self,
+        chunk: &[u8],
+    ) -> impl Iterator<Item = Result<StreamEvent, serde_json::Error>> + use<> {
+        let mut events = Vec::new();
+
+        for &byte in chunk {
+            if std::mem::take(&mut self.skip_lf) && byte == b'\n' {
+                continue;
+            }
+
+            match byte {
+                b'\r' => {
+                    self.skip_lf = true;
+                    self.process_line(&mut events);
+                }
+                b'\n' => self.process_line(&mut events),
+                _ => self.line.push(byte),
+            }
+        }
+
+        events.into_iter()
+    }
+
+    /// Processes a complete line, dispatching an event if the line is empty.
+    fn process_line(&mut self, events: &mut Vec<Result<StreamEvent, serde_json::Error>>) {
+        let mut line = std::mem::take(&mut self.line);
+
+        if !self.started {
+            self.started = true;
+            if line.starts_with(b"\xEF\xBB\xBF") {
+                line.drain(..3);
+            }
+        }
+
+        if line.is_empty() {
+            // Remove the trailing line feed added after the last `data` line. Events with an
+            // empty data buffer are not dispatched.
+            if self.data.pop().is_some() && !self.data.is_empty() {
+                events.push(crate::deserialize_event(&self.data));
+            }
+            self.data.clear();
+            return;
+        }
+
+        // Comments.
+        if line[0] == b':' {
+            return;
+        }
+
+        let (field, value) = match line.iter().position(|&b| b == b':') {
+            Some(pos) => {
+                let value = &line[pos + 1..];
+                (&line[..pos], value.strip_prefix(b" ").unwrap_or(value))
+            }
+            None => (&line[..], &[][..]),
+        };
+
+        // All fields other than `data` (`event`, `id` and `retry`) are not needed.
+        if field == b"data" {
+            self.data.extend_from_slice(value);
+            self.data.push(b'\n');
+        }
+    }
+}
+
+/// Error while accumulating a stream.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    /// The API sent an error event.
+    #[error("API error: {0}")]
+    Api(#[from] ApiError),
+    /// An event was received before the `message_start` event.
+    #[error("received event before message start")]
+    MissingMessageStart,
+    /// An event referenced a content block that was never started.
+    #[error("unknown content block index {0}")]
+    UnknownBlock(u32),
+    /// A content block was started out of order.
+    #[error("unexpected index {0} for new content block")]
+    UnexpectedBlockIndex(u32),
+    /// A delta did not match the type of the content block it was applied to.
+    #[error("delta does not match type of content block {0}")]
+    DeltaMismatch(u32),
+    /// The accumulated tool input was not valid JSON.
+    #[error("invalid tool input JSON in content block {index}")]
+    InvalidToolInput {
+        /// Index of the content block.
+        index: u32,
+        /// The underlying parse error.
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The stream ended before the message was complete.
+    #[error("stream is incomplete")]
+    Incomplete,
+}
+
+/// A content block being assembled.
+#[derive(Debug)]
+struct Block {
+    /// The content assembled so far.
+    content: Content,
+    /// Raw tool input JSON received so far, for tool use blocks.
+    partial_json: String,
+}
+
+/// Assembles streaming events into a complete [`MessagesResponse`].
+///
+/// Events are fed in order through [`StreamAccumulator::push`]. Once the `message_stop` event has
+/// been received, [`StreamAccumulator::finish`] returns the assembled response.
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    /// The message as announced by `message_start`, updated by `message_delta`.
+    message: Option<StreamingMessage>,
+    /// Content blocks, by index.
+    blocks: Vec<Block>,
+    /// Whether the `message_stop` event has been received.
+    complete: bool,
+}
+
+impl StreamAccumulator {
+    /// Creates a new, empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the `message_stop` event has been received.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Returns the message metadata received so far, if the message has started.
+    ///
+    /// Its `content` is always empty, see [`StreamAccumulator::content`] instead.
+    pub fn message(&self) -> Option<&StreamingMessage> {
+        self.message.as_ref()
+    }
+
+    /// Returns the content block at the given index, as assembled so far.
+    ///
+    /// The `input` of tool use blocks is only filled in once the block has stopped.
+    pub fn content(&self, index: u32) -> Option<&Content> {
+        self.block(index).ok().map(|block| &block.content)
+    }
+
+    /// Returns the tool input of the content block at the given index, as received so far.
+    ///
+    /// While the input is still being streamed, the incomplete JSON is parsed on a best-effort
+    /// basis, see [`partial_json`]. Returns `None` if the block is not a tool use block or no
+    /// input could be parsed yet.
+    pub fn partial_input(&self, index: u32) -> Option<Value> {
+        let block = self.block(index).ok()?;
+        match &block.content {
+            Content::ToolUse(ToolUse { input, .. }) | Content::ServerToolUse { input, .. } => {
+                if block.partial_json.is_empty() {
+                    Some(input.clone())
+                } else {
+                    partial_json::parse(&block.partial_json)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Applies a streaming event.
+    ///
+    /// Pings and unknown events are ignored. Error events are returned as
+    /// [`StreamError::Api`].
+    pub fn push(&mut self, event: StreamEvent) -> Result<(), StreamError> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.message = Some(message);
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => {
+                self.message_mut()?;
+                // Blocks are started in order, never trust the index for allocating.
+                if index as usize != self.blocks.len() {
+                    return Err(StreamError::UnexpectedBlockIndex(index));
+                }
+                self.blocks.push(Block {
+                    content: content_block,
+                    partial_json: String::new(),
+                });
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let block = self.block_mut(index)?;
+                match (&mut block.content, delta) {
+                    (Content::Text { text, .. }, Delta::TextDelta { text: fragment }) => {
+                        text.push_str(&fragment);
+                    }
+                    (Content::Text { citations, .. }, Delta::CitationsDelta { citation }) => {
+                        citations.get_or_insert_with(Vec::new).push(citation);
+                    }
+                    (
+                        Content::ToolUse(_) | Content::ServerToolUse { .. },
+                        Delta::InputJsonDelta { partial_json },
+                    ) => {
+                        block.partial_json.push_str(&partial_json);
+                    }
+                    (
+                        Content::Thinking { thinking, .. },
+                        Delta::ThinkingDelta { thinking: fragment },
+                    ) => {
+                        thinking.push_str(&fragment);
+                    }
+                    (
+                        Content::Thinking { signature, .. },
+                        Delta::SignatureDelta {
+                            signature: fragment,
+                        },
+                    ) => {
+                        signature.push_str(&fragment);
+                    }
+                    _ => return Err(StreamError::DeltaMismatch(index)),
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                let block = self.block_mut(index)?;
+                if !block.partial_json.is_empty() {
+                    let input = serde_json::from_str(&block.partial_json)
+                        .map_err(|source| StreamError::InvalidToolInput { index, source })?;
+                    match &mut block.content {
+                        Content::ToolUse(ToolUse { input: target, .. })
+                        | Content::ServerToolUse { input: target, .. } => *target = input,
+                        _ => return Err(StreamError::DeltaMismatch(index)),
+                    }
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                let message = self.message_mut()?;
+                message.update(delta);
+                if let Some(usage) = usage {
+                    apply_usage(message, usage);
+                }
+            }
+            StreamEvent::MessageStop => {
+                self.message_mut()?;
+                self.complete = true;
+            }
+            StreamEvent::Error { error } => return Err(StreamError::Api(error)),
+            StreamEvent::Ping | StreamEvent::Unknown { .. } => {}
+        }
+
+        Ok(())
+    }
+
+    /// Finishes accumulation and returns the complete response.
+    ///
+    /// Fails with [`StreamError::Incomplete`] if the stream has not been completed yet.
+    pub fn finish(self) -> Result<MessagesResponse, StreamError> {
+        if !self.complete {
+            return Err(StreamError::Incomplete);
+        }
+
+        let message = self.message.ok_or(StreamError::MissingMessageStart)?;
+        let stop_reason = message.stop_reason.ok_or(StreamError::Incomplete)?;
+
+        let content = self.blocks.into_iter().map(|block| block.content).collect();
+
+        Ok(MessagesResponse {
+            id: message.id,
+            model: message.model,
+            stop_reason,
+            stop_sequence: message.stop_sequence,
+            usage: message.usage,
+            message: Message {
+                role: message.role,
+                content,
+            },
+        })
+    }
+
+    /// Returns the started message.
+    fn message_mut(&mut self) -> Result<&mut StreamingMessage, StreamError> {
+        self.message
+            .as_mut()
+            .ok_or(StreamError::MissingMessageStart)
+    }
+
+    /// Returns the content block with the given index.
+    fn block(&self, index: u32) -> Result<&Block, StreamError> {
+        self.blocks
+            .get(index as usize)
+            .ok_or(StreamError::UnknownBlock(index))
+    }
+
+    /// Returns the content block with the given index, mutably.
+    fn block_mut(&mut self, index: u32) -> Result<&mut Block, StreamError> {
+        self.blocks
+            .get_mut(index as usize)
+            .ok_or(StreamError::UnknownBlock(index))
+    }
+}
+
+/// Applies streaming usage statistics to a message.
+///
+/// Counts reported by `message_delta` are cumulative, so they replace earlier values.
+fn apply_usage(message: &mut StreamingMessage, usage: StreamingUsage) {
+    let target = &mut message.usage;
+    if let Some(input_tokens) = usage.input_tokens {
+        target.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = usage.output_tokens {
+        target.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = usage.cache_creation_input_tokens {
+        target.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = usage.cache_read_input_tokens {
+        target.cache_read_input_tokens = cache_read_input_tokens;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SseDecoder, StreamAccumulator, StreamError};
+    use crate::{
+        anthropic::{MessagesResponse, StreamEvent},
+        deserialize_event,
+    };
+
+    /// Feeds newline separated event JSON into a new accumulator.
+    fn accumulate(events: &str) -> Result<StreamAccumulator, StreamError> {
+        let mut accumulator = StreamAccumulator::new();
+        for line in events
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let event = deserialize_event(line.as_bytes()).expect("should deserialize event");
+            accumulator.push(event)?;
+        }
+        Ok(accumulator)
+    }
+
+    #[test]
+    fn test_accumulate_matches_non_streaming_response() {
+        let events = r#"
+            {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":472,"output_tokens":2,"cache_creation_input_tokens":0,"cache_read_input_tokens":0}}}
+            {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":"","signature":""}}
+            {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Need the "}}
+            {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"weather."}}
+            {"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQBCgIYAhIM"}}
+            {"type":"content_block_stop","index":0}
+            {"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}
+            {"type":"ping"}
+            {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Let me "}}
+            {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"check."}}
+            {"type":"content_block_stop","index":1}
+            {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}
+            {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":""}}
+            {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"location\": \"San Fra"}}
+            {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"ncisco, CA\"}"}}
+            {"type":"content_block_stop","index":2}
+            {"type":"message_delta","delta":{"stop_reason":"tool_use","stop_sequence":null},"usage":{"output_tokens":89}}
+            {"type":"message_stop"}
+        "#;
+
+        let expected = r#"{"type":"message","id":"msg_1","model":"claude-sonnet-4-20250514","role":"assistant","stop_reason":"tool_use","stop_sequence":null,"usage":{"input_tokens":472,"output_tokens":89,"cache_creation_input_tokens":0,"cache_read_input_tokens":0},"content":[{"type":"thinking","thinking":"Need the weather.","signature":"EqQBCgIYAhIM"},{"type":"text","text":"Let me check."},{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{"location":"San Francisco, CA"}}]}"#;
+        let expected: MessagesResponse =
+            crate::deserialize_response(expected).expect("should deserialize response");
+
+        let accumulator = accumulate(events).expect("should accumulate");
+        assert!(accumulator.is_complete());
+        let response = accumulator.finish().expect("should finish");
+
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_accumulate_server_tool_and_citations() {
+        let events = r#"
+            {"type":"message_start","message":{"id":"msg_2","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"content_block_start","index":0,"content_block":{"type":"server_tool_use","id":"srvtoolu_1","name":"web_search","input":{}}}
+            {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"query\":\"rust\"}"}}
+            {"type":"content_block_stop","index":0}
+            {"type":"content_block_start","index":1,"content_block":{"type":"web_search_tool_result","tool_use_id":"srvtoolu_1","content":[{"type":"web_search_result","title":"Rust","url":"https://www.rust-lang.org/","encrypted_content":"...","page_age":null}]}}
+            {"type":"content_block_stop","index":1}
+            {"type":"content_block_start","index":2,"content_block":{"type":"text","text":""}}
+            {"type":"content_block_delta","index":2,"delta":{"type":"citations_delta","citation":{"type":"web_search_result_location","cited_text":"Rust is fast.","url":"https://www.rust-lang.org/","title":"Rust","encrypted_index":"abc"}}}
+            {"type":"content_block_delta","index":2,"delta":{"type":"text_delta","text":"Rust is fast."}}
+            {"type":"content_block_stop","index":2}
+            {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"output_tokens":20,"server_tool_use":{"web_search_requests":1}}}
+            {"type":"message_stop"}
+        "#;
+
+        let response = accumulate(events)
+            .expect("should accumulate")
+            .finish()
+            .expect("should finish");
+        let content = serde_json::to_value(&response.message.content).unwrap();
+
+        assert_eq!(content[0]["input"]["query"], "rust");
+        assert_eq!(content[1]["content"][0]["title"], "Rust");
+        assert_eq!(content[2]["text"], "Rust is fast.");
+        assert_eq!(content[2]["citations"][0]["cited_text"], "Rust is fast.");
+        assert_eq!(response.usage.output_tokens, 20);
+    }
+
+    #[test]
+    fn test_accumulate_errors() {
+        let before_start =
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#;
+        assert!(matches!(
+            accumulate(before_start),
+            Err(StreamError::MissingMessageStart)
+        ));
+
+        let unfinished = r#"
+            {"type":"message_start","message":{"id":"msg_3","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+        "#;
+        let accumulator = accumulate(unfinished).expect("should accumulate");
+        assert!(!accumulator.is_complete());
+        assert!(matches!(accumulator.finish(), Err(StreamError::Incomplete)));
+
+        let mismatch = r#"
+            {"type":"message_start","message":{"id":"msg_4","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+            {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{}"}}
+        "#;
+        assert!(matches!(
+            accumulate(mismatch),
+            Err(StreamError::DeltaMismatch(0))
+        ));
+
+        let error = r#"
+            {"type":"message_start","message":{"id":"msg_5","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}
+        "#;
+        assert!(matches!(accumulate(error), Err(StreamError::Api(_))));
+
+        let huge_index = r#"
+            {"type":"message_start","message":{"id":"msg_6","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"content_block_start","index":4294967295,"content_block":{"type":"text","text":""}}
+        "#;
+        assert!(matches!(
+            accumulate(huge_index),
+            Err(StreamError::UnexpectedBlockIndex(4294967295))
+        ));
+    }
+
+    #[test]
+    fn test_sse_decoder_splits_events() {
+        let body = concat!(
+            ": comment\n",
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"type\":\"message\",\"role\":\"assistant\",\"content\":[],\"model\":\"claude-sonnet-4-20250514\",\"stop_reason\":null,\"stop_sequence\":null,\"usage\":{\"input_tokens\":10,\"output_tokens\":1}}}\n",
+            "\n",
+            "event: ping\r\n",
+            "data: {\"type\": \"ping\"}\r\n",
+            "\r\n",
+            "event: content_block_delta\r",
+            "data: {\"type\": \"content_block_delta\",\r",
+            "data:\"index\": 0, \"delta\": {\"type\": \"text_delta\", \"text\": \"Hi\"}}\r",
+            "\r",
+            "id: 12\n",
+            "\n",
+            "data: {\"type\": \"message_stop\"}\n",
+            "\n",
+        );
+
+        // Feed the body in every possible chunk size, events must be identical.
+        for chunk_size in 1..body.len() {
+            let mut decoder = SseDecoder::new();
+            let events: Vec<StreamEvent> = body
+                .as_bytes()
+                .chunks(chunk_size)
+                .flat_map(|chunk| decoder.feed(chunk))
+                .collect::<Result<_, _>>()
+                .expect("should decode events");
+
+            assert_eq!(events.len(), 4, "chunk size {}", chunk_size);
+            assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+            assert!(matches!(events[1], StreamEvent::Ping));
+            assert!(matches!(
+                events[2],
+                StreamEvent::ContentBlockDelta { index: 0, .. }
+            ));
+            assert!(matches!(events[3], StreamEvent::MessageStop));
+        }
+    }
+
+    #[test]
+    fn test_sse_decoder_incomplete_and_invalid() {
+        let mut decoder = SseDecoder::new();
+
+        // Byte order mark is skipped, event is held back until the blank line.
+        assert_eq!(
+            decoder
+                .feed(b"\xEF\xBB\xBFdata: {\"type\": \"ping\"}\n")
+                .count(),
+            0
+        );
+        let events: Vec<_> = decoder.feed(b"\n").collect();
+        assert!(matches!(events[..], [Ok(StreamEvent::Ping)]));
+
+        let events: Vec<_> = decoder.feed(b"data: not json\n\n").collect();
+        assert!(matches!(events[..], [Err(_)]));
+
+        // Empty data does not dispatch an event.
+        assert_eq!(decoder.feed(b"data:\n\n").count(), 0);
+        let events: Vec<_> = decoder.feed(b"data: {\"type\": \"ping\"}\n\n").collect();
+        assert!(matches!(events[..], [Ok(StreamEvent::Ping)]));
+    }
+
+    #[test]
+    fn test_partial_input() {
+        let mut accumulator = accumulate(r#"
+            {"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-20250514","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"output_tokens":1}}}
+            {"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"write_file","input":{}}}
+        "#)
+        .expect("should accumulate");
+
+        assert_eq!(accumulator.partial_input(0), Some(serde_json::json!({})));
+        assert_eq!(accumulator.partial_input(1), None);
+
+        let mut expected_content = String::new();
+        for fragment in ["fn ", "main() ", "{}"] {
+            let partial_json = if expected_content.is_empty() {
+                format!(r#"{{"path": "main.rs", "content": "{}"#, fragment)
+            } else {
+                fragment.to_string()
+            };
+            expected_content.push_str(fragment);
+
+            let event = StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: crate::anthropic::Delta::InputJsonDelta { partial_json },
+            };
+            accumulator.push(event).expect("should push");
+
+            assert_eq!(
+                accumulator.partial_input(0),
+                Some(serde_json::json!({"path": "main.rs", "content": expected_content}))
+            );
+        }
+    }
+}
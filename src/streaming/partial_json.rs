@@ -0,0 +1,427 @@
+//! Tolerant parsing of incomplete JSON.
+//!
+//! Tool inputs are streamed as fragments of JSON text (see
+//! [`crate::anthropic::Delta::InputJsonDelta`]), which only form a valid document once the
+//! content block is complete. The [`parse`] function turns a prefix of a JSON document into a
+//! best-effort [`Value`], allowing applications to display tool arguments while they are still
+//! being generated.
+//!
+//! Incomplete parts are completed as follows:
+//!
+//! * Unterminated strings are cut off at the end of the input, incomplete escape sequences are
+//!   dropped.
+//! * Unterminated objects and arrays contain all members parsed so far. An object key without a
+//!   value is omitted.
+//! * Incomplete numbers are parsed as far as possible, e.g. `1.` becomes `1`, a lone `-` is
+//!   omitted.
+//! * Incomplete literals such as `tr` are completed to their full value.
+//!
+//! ## Example
+//!
+//! ```
+//! use claus::streaming::partial_json::parse;
+//! use serde_json::json;
+//!
+//! assert_eq!(
+//!     parse(r#"{"path": "src/main.rs", "content": "fn ma"#),
+//!     Some(json!({"path": "src/main.rs", "content": "fn ma"}))
+//! );
+//! assert_eq!(parse(r#"{"items": [1, 2, tr"#), Some(json!({"items": [1, 2, true]})));
+//! assert_eq!(parse(""), None);
+//! ```
+
+use serde_json::{Map, Number, Value};
+
+/// Parses a possibly incomplete JSON document.
+///
+/// Returns `None` if the input does not contain the beginning of a value yet, or if it is not
+/// the prefix of a valid JSON document. Like `serde_json`, objects and arrays may be nested at most
+/// [`MAX_DEPTH`] levels deep.
+pub fn parse(input: &str) -> Option<Value> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+
+    let value = parser.value().ok()?;
+
+    // Anything but whitespace after a complete value means the input is invalid.
+    parser.skip_whitespace();
+    if parser.pos < parser.input.len() {
+        return None;
+    }
+
+    value
+}
+
+/// Maximum nesting depth of objects and arrays.
+pub const MAX_DEPTH: usize = 128;
+
+/// Marker for input that is not the prefix of a valid JSON document.
+struct Invalid;
+
+/// A recursive descent parser that stops gracefully at the end of the input.
+struct Parser<'a> {
+    /// The input being parsed.
+    input: &'a [u8],
+    /// Current position in the input.
+    pos: usize,
+    /// Number of objects and arrays currently being parsed.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Returns the next byte without consuming it.
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    /// Skips insignificant whitespace.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a value, returning `None` if the input ends before it begins.
+    fn value(&mut self) -> Result<Option<Value>, Invalid> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Ok(None),
+            Some(b'{') => self.nested(Self::object).map(Some),
+            Some(b'[') => self.nested(Self::array).map(Some),
+            Some(b'"') => self.string().map(|(s, _)| Some(Value::String(s))),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(Invalid),
+        }
+    }
+
+    /// Parses an object or array using `parse`, enforcing the nesting limit.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, Invalid>) -> Result<Value, Invalid> {
+        if self.depth == MAX_DEPTH {
+            return Err(Invalid);
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    /// Consumes the separator between two members of an object or array.
+    ///
+    /// Returns `false` if the input ends before the next member.
+    fn separator(&mut self) -> Result<bool, Invalid> {
+        if self.peek() != Some(b',') {
+            return Err(Invalid);
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        Ok(self.peek().is_some())
+    }
+
+    /// Parses an object, the current byte must be `{`.
+    fn object(&mut self) -> Result<Value, Invalid> {
+        self.pos += 1;
+        let mut map = Map::new();
+        let mut first = true;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {}
+            }
+
+            if !std::mem::take(&mut first) && !self.separator()? {
+                break;
+            }
+
+            if self.peek() != Some(b'"') {
+                return Err(Invalid);
+            }
+            let (key, complete) = self.string()?;
+            if !complete {
+                break;
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(b':') => self.pos += 1,
+                Some(_) => return Err(Invalid),
+            }
+
+            match self.value()? {
+                Some(value) => {
+                    map.insert(key, value);
+                }
+                None => break,
+            }
+        }
+
+        Ok(Value::Object(map))
+    }
+
+    /// Parses an array, the current byte must be `[`.
+    fn array(&mut self) -> Result<Value, Invalid> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        let mut first = true;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {}
+            }
+
+            if !std::mem::take(&mut first) && !self.separator()? {
+                break;
+            }
+
+            match self.value()? {
+                Some(value) => items.push(value),
+                None => break,
+            }
+        }
+
+        Ok(Value::Array(items))
+    }
+
+    /// Parses a string, the current byte must be `"`.
+    ///
+    /// Stops at the end of input if the string is unterminated. Returns the string and whether it
+    /// was terminated.
+    fn string(&mut self) -> Result<(String, bool), Invalid> {
+        self.pos += 1;
+        let mut buf = Vec::new();
+
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'"' => return Ok((into_string(buf), true)),
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => match self.unicode_escape()? {
+                            Some(c) => c,
+                            None => break,
+                        },
+                        _ => return Err(Invalid),
+                    };
+                    let mut encoded = [0; 4];
+                    buf.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                }
+                _ => buf.push(byte),
+            }
+        }
+
+        Ok((into_string(buf), false))
+    }
+
+    /// Parses the hex digits of a `\u` escape, including a following low surrogate.
+    ///
+    /// Returns `None` if the input ends within the escape sequence.
+    fn unicode_escape(&mut self) -> Result<Option<char>, Invalid> {
+        let Some(high) = self.hex4()? else {
+            return Ok(None);
+        };
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(Some(
+                char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER),
+            ));
+        }
+
+        // A high surrogate must be followed by an escaped low surrogate.
+        let remaining = &self.input[self.pos..];
+        if remaining.len() < 2 && b"\\u".starts_with(remaining) {
+            self.pos = self.input.len();
+            return Ok(None);
+        }
+        if !remaining.starts_with(b"\\u") {
+            return Ok(Some(char::REPLACEMENT_CHARACTER));
+        }
+        self.pos += 2;
+
+        let Some(low) = self.hex4()? else {
+            return Ok(None);
+        };
+        if !(0xDC00..0xE000).contains(&low) {
+            return Ok(Some(char::REPLACEMENT_CHARACTER));
+        }
+        let combined = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        Ok(Some(
+            char::from_u32(combined).unwrap_or(char::REPLACEMENT_CHARACTER),
+        ))
+    }
+
+    /// Parses four hex digits, returning `None` if the input ends before.
+    fn hex4(&mut self) -> Result<Option<u32>, Invalid> {
+        let Some(digits) = self.input.get(self.pos..self.pos + 4) else {
+            if self.input[self.pos..].iter().all(u8::is_ascii_hexdigit) {
+                self.pos = self.input.len();
+                return Ok(None);
+            }
+            return Err(Invalid);
+        };
+
+        // `from_str_radix` would also accept a leading sign.
+        if !digits.iter().all(u8::is_ascii_hexdigit) {
+            return Err(Invalid);
+        }
+        let digits = std::str::from_utf8(digits).map_err(|_| Invalid)?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| Invalid)?;
+        self.pos += 4;
+        Ok(Some(value))
+    }
+
+    /// Parses a literal, completing it if the input ends early.
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Option<Value>, Invalid> {
+        let remaining = &self.input[self.pos..];
+        let len = remaining.len().min(literal.len());
+
+        if remaining[..len] != literal.as_bytes()[..len] {
+            return Err(Invalid);
+        }
+
+        self.pos += len;
+        Ok(Some(value))
+    }
+
+    /// Parses a number, cutting off incomplete trailing parts.
+    fn number(&mut self) -> Result<Option<Value>, Invalid> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+
+        let text = std::str::from_utf8(&self.input[start..self.pos]).map_err(|_| Invalid)?;
+        if let Some(number) = parse_number(text) {
+            return Ok(Some(Value::Number(number)));
+        }
+
+        // Only numbers at the end of the input may still be continued.
+        if self.pos == self.input.len() {
+            let trimmed = text.trim_end_matches(['-', '+', '.', 'e', 'E']);
+            if trimmed.is_empty() {
+                return Ok(None);
+            }
+            if let Some(number) = parse_number(trimmed) {
+                return Ok(Some(Value::Number(number)));
+            }
+        }
+
+        Err(Invalid)
+    }
+}
+
+/// Parses a complete JSON number.
+fn parse_number(text: &str) -> Option<Number> {
+    if text.is_empty() {
+        return None;
+    }
+    serde_json::from_str(text).ok()
+}
+
+/// Converts string contents into a [`String`].
+fn into_string(buf: Vec<u8>) -> String {
+    String::from_utf8(buf)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::parse;
+
+    #[test]
+    fn test_parse_every_prefix() {
+        let document = r#"{"path": "src/é\n.rs", "lines": [1, -2.5e3, true, null], "nested": {"ok": false}, "emoji": "😀"}"#;
+        let complete: serde_json::Value = serde_json::from_str(document).unwrap();
+
+        // Every prefix must parse to something, or be too short to contain a value.
+        for end in (0..=document.len()).filter(|&end| document.is_char_boundary(end)) {
+            let prefix = &document[..end];
+            let parsed = parse(prefix);
+            if end == 0 {
+                assert_eq!(parsed, None);
+            } else {
+                assert!(
+                    matches!(parsed, Some(serde_json::Value::Object(_))),
+                    "prefix {:?} parsed as {:?}",
+                    prefix,
+                    parsed
+                );
+            }
+        }
+
+        assert_eq!(parse(document), Some(complete));
+    }
+
+    #[test]
+    fn test_parse_partial_values() {
+        assert_eq!(parse(r#"{"a": "hel"#), Some(json!({"a": "hel"})));
+        assert_eq!(parse(r#"{"a": "x\"#), Some(json!({"a": "x"})));
+        assert_eq!(parse(r#"{"a": "\u00"#), Some(json!({"a": ""})));
+        assert_eq!(parse(r#"{"a": 1, "b"#), Some(json!({"a": 1})));
+        assert_eq!(parse(r#"{"a": 1, "b": "#), Some(json!({"a": 1})));
+        assert_eq!(parse(r#"{"a": 1."#), Some(json!({"a": 1})));
+        assert_eq!(parse(r#"{"a": -"#), Some(json!({})));
+        assert_eq!(parse(r#"{"a": [1, 2"#), Some(json!({"a": [1, 2]})));
+        assert_eq!(parse(r#"{"a": [1, 2,"#), Some(json!({"a": [1, 2]})));
+        assert_eq!(parse(r#"{"a": fa"#), Some(json!({"a": false})));
+        assert_eq!(parse("  "), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse(r#"{"a": x"#), None);
+        assert_eq!(parse(r#"{"a": tx"#), None);
+        assert_eq!(parse(r#"{"a" 1}"#), None);
+        assert_eq!(parse(r#"{"a": 1} 2"#), None);
+        assert_eq!(parse(r#"[1 2]"#), None);
+        assert_eq!(parse(r#"["\u+123"]"#), None);
+        assert_eq!(parse(r#"["\u-12"#), None);
+    }
+
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nested = "[".repeat(super::MAX_DEPTH);
+        assert!(parse(&nested).is_some());
+
+        let too_deep = format!("{nested}[");
+        assert_eq!(parse(&too_deep), None);
+
+        // Deeply nested input must not overflow the stack.
+        assert_eq!(parse(&"[{\"a\":".repeat(500_000)), None);
+    }
+}